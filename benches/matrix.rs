@@ -0,0 +1,145 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use sim_block::model::Matrix;
+
+// The previous implementation, one heap allocated `Vector` per row, with the
+// operations as they were written then. Kept here as the baseline.
+mod previous {
+    use rayon::prelude::*;
+    use sim_block::model::Vector;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone)]
+    pub struct Matrix {
+        data: Vec<Vector>,
+    }
+
+    impl Matrix {
+        pub fn from_fn(n: usize, f: impl Fn(usize, usize) -> f64) -> Self {
+            Self {
+                data: (0..n)
+                    .map(|i| Vector::from((0..n).map(|j| f(i, j)).collect::<Vec<_>>()))
+                    .collect(),
+            }
+        }
+
+        pub fn get(&self, (i, j): (usize, usize)) -> f64 {
+            self.data[i][j]
+        }
+
+        pub fn ravel(&self) -> Vector {
+            let mut data = self.data.clone();
+            let mut new_data = Vec::new();
+            data.iter_mut()
+                .for_each(|x| new_data.extend(x.data().to_vec()));
+            Vector::from(new_data)
+        }
+
+        pub fn sum(&self) -> Vector {
+            let matrix = self.clone();
+            let res = Vector::zero(self.data[0].dim());
+            let res_lock = Arc::new(Mutex::new(res));
+            matrix
+                .data
+                .par_iter()
+                .for_each(|x| *(res_lock.lock().unwrap()) += x.clone());
+            Arc::try_unwrap(res_lock).unwrap().into_inner().unwrap()
+        }
+
+        pub fn add(self, rhs: Self) -> Self {
+            Self {
+                data: self
+                    .data
+                    .par_iter()
+                    .zip(rhs.data.par_iter())
+                    .map(|(a, b)| a.clone() + b.clone())
+                    .collect(),
+            }
+        }
+
+        pub fn scale(self, rhs: f64) -> Self {
+            Self {
+                data: self.data.par_iter().map(|a| a.clone() * rhs).collect(),
+            }
+        }
+    }
+}
+
+fn value(n: usize) -> impl Fn(usize, usize) -> f64 {
+    move |i, j| (i * n + j) as f64
+}
+
+fn matrices(n: usize) -> (previous::Matrix, Matrix) {
+    (
+        previous::Matrix::from_fn(n, value(n)),
+        Matrix::from_fn((n, n), value(n)),
+    )
+}
+
+fn bench_ravel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ravel");
+    for n in [4, 64, 512] {
+        let (old, m) = matrices(n);
+        group.bench_with_input(BenchmarkId::new("previous", n), &old, |b, old| {
+            b.iter(|| black_box(old.ravel()))
+        });
+        group.bench_with_input(BenchmarkId::new("contiguous", n), &m, |b, m| {
+            b.iter(|| black_box(m.ravel()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_sum(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sum");
+    for n in [4, 64, 512] {
+        let (old, m) = matrices(n);
+        group.bench_with_input(BenchmarkId::new("previous", n), &old, |b, old| {
+            b.iter(|| black_box(old.sum()))
+        });
+        group.bench_with_input(BenchmarkId::new("contiguous", n), &m, |b, m| {
+            b.iter(|| black_box(m.sum()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_access(c: &mut Criterion) {
+    let mut group = c.benchmark_group("element_access");
+    for n in [4, 64, 512] {
+        let (old, m) = matrices(n);
+        let indices = move || (0..n).flat_map(move |i| (0..n).map(move |j| (i, j)));
+        group.bench_with_input(BenchmarkId::new("previous", n), &old, |b, old| {
+            b.iter(|| indices().map(|idx| old.get(idx)).sum::<f64>())
+        });
+        group.bench_with_input(BenchmarkId::new("contiguous", n), &m, |b, m| {
+            b.iter(|| indices().map(|idx| m[idx]).sum::<f64>())
+        });
+    }
+    group.finish();
+}
+
+fn bench_ops(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ops");
+    for n in [4, 64, 256] {
+        let (old, m) = matrices(n);
+        group.bench_with_input(BenchmarkId::new("add/previous", n), &old, |b, old| {
+            b.iter(|| black_box(old.clone().add(old.clone())))
+        });
+        group.bench_with_input(BenchmarkId::new("add/contiguous", n), &m, |b, m| {
+            b.iter(|| black_box(m.clone() + m.clone()))
+        });
+        group.bench_with_input(BenchmarkId::new("scale/previous", n), &old, |b, old| {
+            b.iter(|| black_box(old.clone().scale(2.0)))
+        });
+        group.bench_with_input(BenchmarkId::new("scale/contiguous", n), &m, |b, m| {
+            b.iter(|| black_box(m.clone() * 2.0))
+        });
+        group.bench_with_input(BenchmarkId::new("matmul", n), &m, |b, m| {
+            b.iter(|| black_box(m.matmul(m)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_ravel, bench_sum, bench_access, bench_ops);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use sim_block::model::Vector;
+
+const DIMS: [usize; 6] = [3, 16, 256, 4096, 1 << 16, 1 << 20];
+
+fn vector(n: usize) -> Vector {
+    Vector::from((0..n).map(|i| (i as f64).sin()).collect::<Vec<_>>())
+}
+
+// Runs `f` once with the default threshold and once with every size sent to
+// rayon, which is what these operations always did before.
+fn compare(c: &mut Criterion, name: &str, f: impl Fn(&Vector) -> Vector) {
+    let default = Vector::parallel_threshold();
+    let mut group = c.benchmark_group(name);
+    for n in DIMS {
+        let v = vector(n);
+        for (label, threshold) in [("adaptive", default), ("rayon", 0)] {
+            group.bench_with_input(BenchmarkId::new(label, n), &v, |b, v| {
+                Vector::set_parallel_threshold(threshold);
+                b.iter(|| black_box(f(v)));
+                Vector::set_parallel_threshold(default);
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_norm(c: &mut Criterion) {
+    compare(c, "norm", |v| Vector::from(vec![v.norm()]));
+}
+
+fn bench_dot(c: &mut Criterion) {
+    compare(c, "dot", |v| Vector::from(vec![v.dot(v)]));
+}
+
+fn bench_fill(c: &mut Criterion) {
+    compare(c, "fill", |v| {
+        let mut v = v.clone();
+        v.fill(1.0);
+        v
+    });
+}
+
+fn bench_map(c: &mut Criterion) {
+    compare(c, "map", |v| v.map(|x| 2.0 * x + 1.0));
+}
+
+fn bench_abs(c: &mut Criterion) {
+    compare(c, "abs", |v| v.abs());
+}
+
+fn bench_normalize(c: &mut Criterion) {
+    compare(c, "normalize", |v| v.normalize());
+}
+
+criterion_group!(
+    benches,
+    bench_norm,
+    bench_dot,
+    bench_fill,
+    bench_map,
+    bench_abs,
+    bench_normalize
+);
+criterion_main!(benches);
@@ -2,10 +2,34 @@ use crate::model::IsValue;
 
 pub trait Source<V: IsValue> {
     fn output(&self, t: f64) -> V;
+
+    /// As `Block::zero_crossings`, so sources added with
+    /// `Diagram::add_source` keep their events.
+    fn zero_crossings(&self, _t: f64) -> Vec<f64> {
+        Vec::new()
+    }
+}
+
+impl<S, V> Source<V> for Box<S>
+where
+    S: Source<V> + ?Sized,
+    V: IsValue,
+{
+    fn output(&self, t: f64) -> V {
+        (**self).output(t)
+    }
+
+    fn zero_crossings(&self, t: f64) -> Vec<f64> {
+        (**self).zero_crossings(t)
+    }
 }
 
 pub trait SourceMut<V: IsValue> {
     fn output_mut(&mut self, t: f64) -> V;
+
+    fn zero_crossings(&self, _t: f64) -> Vec<f64> {
+        Vec::new()
+    }
 }
 
 impl<T, V> SourceMut<V> for T
@@ -16,14 +40,24 @@ where
     fn output_mut(&mut self, t: f64) -> V {
         self.output(t)
     }
+
+    fn zero_crossings(&self, t: f64) -> Vec<f64> {
+        Source::zero_crossings(self, t)
+    }
 }
 
 pub trait Sink<V: IsValue> {
     fn input(&self, t: f64, value: &V);
+
+    /// As `Block::reset`, called on the sink added with `Diagram::add_sink`.
+    fn reset(&mut self) {}
 }
 
 pub trait SinkMut<V: IsValue> {
     fn input_mut(&mut self, t: f64, value: &V);
+
+    /// As `Block::reset`, called on the sink added with `Diagram::add_sink`.
+    fn reset_mut(&mut self) {}
 }
 
 impl<T, V> SinkMut<V> for T
@@ -34,14 +68,28 @@ where
     fn input_mut(&mut self, t: f64, value: &V) {
         self.input(t, value)
     }
+
+    fn reset_mut(&mut self) {
+        Sink::reset(self)
+    }
 }
 
 pub trait Transfer<Vi: IsValue, Vo: IsValue> {
     fn transfer(&self, t: f64, input: &Vi) -> Vo;
+
+    /// As `Block::zero_crossings`, so transfers added with
+    /// `Diagram::add_transfer` keep their events.
+    fn zero_crossings(&self, _t: f64, _input: &Vi) -> Vec<f64> {
+        Vec::new()
+    }
 }
 
 pub trait TransferMut<Vi: IsValue, Vo: IsValue> {
     fn transfer_mut(&mut self, t: f64, input: &Vi) -> Vo;
+
+    fn zero_crossings(&self, _t: f64, _input: &Vi) -> Vec<f64> {
+        Vec::new()
+    }
 }
 
 impl<T, Vi, Vo> TransferMut<Vi, Vo> for T
@@ -53,4 +101,171 @@ where
     fn transfer_mut(&mut self, t: f64, input: &Vi) -> Vo {
         self.transfer(t, input)
     }
+
+    fn zero_crossings(&self, t: f64, input: &Vi) -> Vec<f64> {
+        Transfer::zero_crossings(self, t, input)
+    }
+}
+
+pub trait Block<V: IsValue> {
+    fn num_inputs(&self) -> usize;
+    fn num_outputs(&self) -> usize;
+    fn output(&mut self, t: f64, inputs: &[V]) -> Vec<V>;
+
+    /// Whether `output` reads `inputs`. Blocks without direct feedthrough are
+    /// evaluated with no inputs and break algebraic loops.
+    fn direct_feedthrough(&self) -> bool {
+        true
+    }
+
+    /// Called once per step after every block has produced its outputs.
+    fn update(&mut self, _t: f64, _inputs: &[V]) {}
+
+    /// Restores the state the block was created with. `Simulation::run`
+    /// resets every block before it starts.
+    fn reset(&mut self) {}
+
+    /// Signals whose sign changes mark discontinuities that the simulation
+    /// should step onto exactly.
+    fn zero_crossings(&self, _t: f64, _inputs: &[V]) -> Vec<f64> {
+        Vec::new()
+    }
+
+    /// Discrete and triggered blocks are only updated at their sample hits,
+    /// which the simulation steps onto, and the outputs of those with direct
+    /// feedthrough are held between hits. A triggered block is hit when one of
+    /// its zero crossing signals changes sign along its edge.
+    fn sample_time(&self) -> SampleTime {
+        SampleTime::Continuous
+    }
+
+    /// Receives the sample time resolved from the driving blocks when
+    /// `sample_time` is `SampleTime::Inherited`.
+    fn inherit_sample_time(&mut self, _sample_time: SampleTime) {}
+
+    fn continuous_state(&self) -> Option<&dyn ContinuousState<V>> {
+        None
+    }
+
+    fn continuous_state_mut(&mut self) -> Option<&mut dyn ContinuousState<V>> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerEdge {
+    Rising,
+    Falling,
+    Either,
+}
+
+impl TriggerEdge {
+    pub fn fired(&self, before: &[f64], after: &[f64]) -> bool {
+        before.iter().zip(after.iter()).any(|(a, b)| {
+            let rising = *a < 0.0 && *b >= 0.0;
+            let falling = *a > 0.0 && *b <= 0.0;
+            match self {
+                TriggerEdge::Rising => rising,
+                TriggerEdge::Falling => falling,
+                TriggerEdge::Either => rising || falling,
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleTime {
+    Continuous,
+    // Output computed at every major step and held through the solver stages
+    // and event location in between.
+    FixedInMinorStep,
+    Inherited,
+    Discrete { period: f64, offset: f64 },
+    Triggered(TriggerEdge),
+}
+
+impl SampleTime {
+    pub fn discrete(period: f64) -> Self {
+        SampleTime::Discrete {
+            period,
+            offset: 0.0,
+        }
+    }
+
+    pub fn is_discrete(&self) -> bool {
+        matches!(self, SampleTime::Discrete { .. })
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        matches!(self, SampleTime::Triggered(_))
+    }
+
+    // Whether outputs are only recomputed at hits.
+    pub fn holds_output(&self) -> bool {
+        !matches!(self, SampleTime::Continuous | SampleTime::Inherited)
+    }
+
+    // Triggered hits depend on the signals and are not known here.
+    pub fn is_hit(&self, t: f64) -> bool {
+        match *self {
+            SampleTime::Continuous | SampleTime::FixedInMinorStep => true,
+            SampleTime::Discrete { period, offset } => {
+                let n = ((t - offset) / period).round();
+                n >= 0.0 && (offset + n * period - t).abs() <= hit_tolerance(t, period)
+            }
+            SampleTime::Inherited | SampleTime::Triggered(_) => false,
+        }
+    }
+
+    // First hit strictly after `t`, ignoring hits within the tolerance.
+    pub fn next_hit(&self, t: f64) -> Option<f64> {
+        match *self {
+            SampleTime::Continuous
+            | SampleTime::FixedInMinorStep
+            | SampleTime::Inherited
+            | SampleTime::Triggered(_) => None,
+            SampleTime::Discrete { period, offset } => {
+                if t < offset - hit_tolerance(t, period) {
+                    return Some(offset);
+                }
+                let mut next = offset + (((t - offset) / period).floor() + 1.0) * period;
+                if next - t <= hit_tolerance(t, period) {
+                    next += period;
+                }
+                Some(next)
+            }
+        }
+    }
+}
+
+fn hit_tolerance(t: f64, period: f64) -> f64 {
+    1e-10 * t.abs().max(period)
+}
+
+// Greatest common divisor of two periods, within a relative tolerance.
+pub(crate) fn period_gcd(a: f64, b: f64) -> f64 {
+    let (mut a, mut b) = (a.max(b), a.min(b));
+    let tolerance = 1e-9 * a;
+    while b > tolerance {
+        let r = a % b;
+        if r <= tolerance || b - r <= tolerance {
+            return b;
+        }
+        (a, b) = (b, r);
+    }
+    a
+}
+
+pub trait ContinuousState<V: IsValue> {
+    fn state(&self) -> Vec<f64>;
+
+    fn set_state(&mut self, state: &[f64]);
+
+    /// Time derivative of `x` for the inputs `u`, without touching the state
+    /// held by the block.
+    fn derivative(&self, t: f64, x: &[f64], u: &[V]) -> Vec<f64>;
+
+    fn num_states(&self) -> usize {
+        self.state().len()
+    }
 }
@@ -1,25 +1,148 @@
-use crate::TransferMut;
+use crate::{
+    block::{Block, TransferMut},
+    utils::SimError,
+};
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DerivativeMethod {
+    // Derivative of the polynomial through the current sample and the given
+    // number (1 to 4) of previous ones, which allows non-uniform steps.
+    Backward(usize),
+    // s / (tau s + 1), computed exactly for an input that is linear between
+    // samples.
+    Filtered(f64),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    t: f64,
+    u: f64,
+    // Low-pass state of the filtered method.
+    lag: f64,
+}
+
+// Outputs `initial_output` until a sample with an earlier time is available,
+// rather than differencing the first sample against an assumed initial input
+// at t = 0. A sample at an already seen time replaces the older one, as does
+// any later history when time goes backwards. In a diagram the history only
+// grows in `update`.
 #[derive(Debug, Clone)]
 pub struct Differentiator {
-    last_value: f64,
-    last_time: f64,
+    initial_output: f64,
+    method: DerivativeMethod,
+    history: Vec<Sample>,
 }
 
 impl Differentiator {
-    pub fn new(init: f64) -> Self {
+    pub fn new(initial_output: f64) -> Self {
         Self {
-            last_value: init,
-            last_time: 0.0,
+            initial_output,
+            method: DerivativeMethod::Backward(1),
+            history: Vec::new(),
         }
     }
 
+    pub fn with_method(initial_output: f64, method: DerivativeMethod) -> Result<Self, SimError> {
+        match method {
+            DerivativeMethod::Backward(order) if !(1..=4).contains(&order) => {
+                return Err(SimError::InvalidParameter(format!(
+                    "backward difference order must be between 1 and 4, got {order}"
+                )))
+            }
+            DerivativeMethod::Filtered(tau) if !(tau > 0.0 && tau.is_finite()) => {
+                return Err(SimError::InvalidParameter(format!(
+                    "filter time constant must be positive, got {tau}"
+                )))
+            }
+            _ => {}
+        }
+        Ok(Self {
+            initial_output,
+            method,
+            history: Vec::new(),
+        })
+    }
+
+    pub fn initial_output(&self) -> f64 {
+        self.initial_output
+    }
+
+    pub fn method(&self) -> DerivativeMethod {
+        self.method
+    }
+
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+
     pub fn differentiate(&mut self, t: f64, value: f64) -> f64 {
-        let res = (value - self.last_value) / (t - self.last_time);
-        self.last_value = value;
-        self.last_time = t;
+        let res = self.derivative(t, value);
+        self.push(t, value);
         res
     }
+
+    // Samples strictly before `t`.
+    fn past(&self, t: f64) -> &[Sample] {
+        &self.history[..self.history.partition_point(|s| s.t < t)]
+    }
+
+    fn derivative(&self, t: f64, u: f64) -> f64 {
+        let past = self.past(t);
+        let Some(last) = past.last() else {
+            return self.initial_output;
+        };
+        match self.method {
+            DerivativeMethod::Backward(order) => {
+                let past = &past[past.len().saturating_sub(order)..];
+                backward_weights(past, t)
+                    .iter()
+                    .zip(past.iter().map(|s| s.u).chain([u]))
+                    .map(|(w, u)| w * u)
+                    .sum()
+            }
+            DerivativeMethod::Filtered(tau) => (u - lag(last, t, u, tau)) / tau,
+        }
+    }
+
+    fn push(&mut self, t: f64, u: f64) {
+        let n = self.past(t).len();
+        self.history.truncate(n);
+        let (lag, capacity) = match (self.method, self.history.last()) {
+            (DerivativeMethod::Backward(order), _) => (u, order + 1),
+            (DerivativeMethod::Filtered(tau), Some(last)) => (lag(last, t, u, tau), 2),
+            (DerivativeMethod::Filtered(_), None) => (u, 2),
+        };
+        self.history.push(Sample { t, u, lag });
+        if self.history.len() > capacity {
+            self.history.remove(0);
+        }
+    }
+}
+
+// State at `t` of `tau dx/dt = u - x`, starting from `last` with the input
+// linear in between.
+fn lag(last: &Sample, t: f64, u: f64, tau: f64) -> f64 {
+    let h = t - last.t;
+    let rate = (u - last.u) / h;
+    u - tau * rate + (last.lag - last.u + tau * rate) * (-h / tau).exp()
+}
+
+// Weights of the derivative at `t` of the Lagrange polynomial through the
+// samples and the point at `t`, in that order.
+fn backward_weights(past: &[Sample], t: f64) -> Vec<f64> {
+    let ts = past.iter().map(|s| s.t).chain([t]).collect::<Vec<_>>();
+    let k = ts.len() - 1;
+    let mut weights = (0..k)
+        .map(|j| {
+            let mut w = 1.0 / (ts[j] - ts[k]);
+            for m in (0..k).filter(|m| *m != j) {
+                w *= (ts[k] - ts[m]) / (ts[j] - ts[m]);
+            }
+            w
+        })
+        .collect::<Vec<_>>();
+    weights.push((0..k).map(|m| 1.0 / (ts[k] - ts[m])).sum());
+    weights
 }
 
 impl TransferMut<f64, f64> for Differentiator {
@@ -27,3 +150,226 @@ impl TransferMut<f64, f64> for Differentiator {
         self.differentiate(t, *input)
     }
 }
+
+impl Block<f64> for Differentiator {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        vec![self.derivative(t, inputs[0])]
+    }
+
+    fn update(&mut self, t: f64, inputs: &[f64]) {
+        self.push(t, inputs[0]);
+    }
+
+    fn reset(&mut self) {
+        Differentiator::reset(self)
+    }
+}
+
+#[cfg(feature = "vector")]
+pub(crate) mod vector {
+    use super::{DerivativeMethod, Differentiator};
+    use crate::{
+        block::{Block, TransferMut},
+        model::Vector,
+        utils::SimError,
+    };
+
+    #[derive(Debug, Clone)]
+    pub struct VectorDifferentiator {
+        method: DerivativeMethod,
+        channels: Vec<Differentiator>,
+    }
+
+    impl VectorDifferentiator {
+        pub fn new(initial_output: impl Into<Vector>) -> Self {
+            let initial_output = initial_output.into();
+            Self {
+                method: DerivativeMethod::Backward(1),
+                channels: initial_output
+                    .data()
+                    .iter()
+                    .map(|x| Differentiator::new(*x))
+                    .collect(),
+            }
+        }
+
+        pub fn with_method(
+            initial_output: impl Into<Vector>,
+            method: DerivativeMethod,
+        ) -> Result<Self, SimError> {
+            let initial_output = initial_output.into();
+            Ok(Self {
+                method,
+                channels: initial_output
+                    .data()
+                    .iter()
+                    .map(|x| Differentiator::with_method(*x, method))
+                    .collect::<Result<_, _>>()?,
+            })
+        }
+
+        pub fn initial_output(&self) -> Vector {
+            Vector::from(
+                self.channels
+                    .iter()
+                    .map(|c| c.initial_output)
+                    .collect::<Vec<_>>(),
+            )
+        }
+
+        pub fn method(&self) -> DerivativeMethod {
+            self.method
+        }
+
+        pub fn reset(&mut self) {
+            self.channels.iter_mut().for_each(|c| c.reset());
+        }
+
+        fn check(&self, input: &Vector) -> Result<(), SimError> {
+            if input.dim() != self.channels.len() {
+                return Err(SimError::DimensionMismatch {
+                    expected: self.channels.len(),
+                    found: input.dim(),
+                });
+            }
+            Ok(())
+        }
+
+        fn derivative(&self, t: f64, input: &Vector) -> Vector {
+            self.check(input).unwrap_or_else(|e| panic!("{}", e));
+            Vector::from(
+                self.channels
+                    .iter()
+                    .zip(input.data())
+                    .map(|(c, u)| c.derivative(t, *u))
+                    .collect::<Vec<_>>(),
+            )
+        }
+
+        fn push(&mut self, t: f64, input: &Vector) {
+            for (c, u) in self.channels.iter_mut().zip(input.data()) {
+                c.push(t, *u);
+            }
+        }
+    }
+
+    impl TransferMut<Vector, Vector> for VectorDifferentiator {
+        fn transfer_mut(&mut self, t: f64, input: &Vector) -> Vector {
+            let res = self.derivative(t, input);
+            self.push(t, input);
+            res
+        }
+    }
+
+    impl Block<Vector> for VectorDifferentiator {
+        fn num_inputs(&self) -> usize {
+            1
+        }
+
+        fn num_outputs(&self) -> usize {
+            1
+        }
+
+        fn output(&mut self, t: f64, inputs: &[Vector]) -> Vec<Vector> {
+            vec![self.derivative(t, &inputs[0])]
+        }
+
+        fn update(&mut self, t: f64, inputs: &[Vector]) {
+            self.push(t, &inputs[0]);
+        }
+
+        fn reset(&mut self) {
+            VectorDifferentiator::reset(self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod differentiator_tests {
+    use super::*;
+
+    #[test]
+    fn test_first_call() {
+        let mut d = Differentiator::new(0.0);
+        assert_eq!(d.transfer_mut(0.0, &5.0), 0.0);
+        assert_eq!(d.transfer_mut(0.5, &6.0), 2.0);
+        // A repeated time replaces the sample instead of dividing by zero.
+        assert_eq!(d.transfer_mut(0.5, &7.0), 4.0);
+        assert_eq!(d.transfer_mut(1.0, &7.0), 0.0);
+        d.reset();
+        assert_eq!(d.transfer_mut(1.0, &7.0), 0.0);
+    }
+
+    #[test]
+    fn test_backward() {
+        // Order k is exact for polynomials of degree k, also on uneven steps.
+        let times = [0.0, 0.1, 0.3, 0.35, 0.6, 1.0];
+        let cubic = |t: f64| t * t * t - 2.0 * t;
+        let mut d = Differentiator::with_method(0.0, DerivativeMethod::Backward(3)).unwrap();
+        let mut y = 0.0;
+        for t in times {
+            y = d.transfer_mut(t, &cubic(t));
+        }
+        assert!((y - 1.0).abs() < 1e-9);
+        let mut first = Differentiator::new(0.0);
+        for t in times {
+            y = first.transfer_mut(t, &cubic(t));
+        }
+        assert!((y - 1.0).abs() > 0.1);
+        assert!(Differentiator::with_method(0.0, DerivativeMethod::Backward(5)).is_err());
+    }
+
+    #[test]
+    fn test_filtered() {
+        // A ramp of slope 2 through s / (tau s + 1) gives 2 (1 - e^(-t/tau)).
+        let tau = 0.1;
+        let mut d = Differentiator::with_method(0.0, DerivativeMethod::Filtered(tau)).unwrap();
+        let mut y = 0.0;
+        for k in 0..=10 {
+            let t = k as f64 * 0.05;
+            y = d.transfer_mut(t, &(2.0 * t));
+        }
+        assert!((y - 2.0 * (1.0 - (-0.5f64 / tau).exp())).abs() < 1e-12);
+        assert!(Differentiator::with_method(0.0, DerivativeMethod::Filtered(0.0)).is_err());
+
+        // The output stays bounded on a noisy input where the plain
+        // difference does not.
+        let noisy = |k: usize| if k.is_multiple_of(2) { 1e-3 } else { -1e-3 };
+        let mut filtered =
+            Differentiator::with_method(0.0, DerivativeMethod::Filtered(tau)).unwrap();
+        let mut plain = Differentiator::new(0.0);
+        let (mut a, mut b) = (0.0f64, 0.0f64);
+        for k in 0..100 {
+            let t = k as f64 * 1e-3;
+            a = a.max(filtered.transfer_mut(t, &noisy(k)).abs());
+            b = b.max(plain.transfer_mut(t, &noisy(k)).abs());
+        }
+        assert!(a < 0.05 && b > 1.0);
+    }
+
+    #[cfg(feature = "vector")]
+    #[test]
+    fn test_vector_differentiator() {
+        use super::vector::VectorDifferentiator;
+        use crate::model::Vector;
+
+        let mut d =
+            VectorDifferentiator::with_method(vec![0.0, 0.0], DerivativeMethod::Backward(2))
+                .unwrap();
+        let mut y = Vector::zero(2);
+        for k in 0..=10 {
+            let t = k as f64 * 0.1;
+            y = d.transfer_mut(t, &Vector::from(vec![t * t, 3.0 * t]));
+        }
+        assert!((y[0] - 2.0).abs() < 1e-9 && (y[1] - 3.0).abs() < 1e-9);
+        assert_eq!(d.initial_output(), Vector::zero(2));
+    }
+}
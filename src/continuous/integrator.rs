@@ -1,195 +1,355 @@
-use crate::block::TransferMut;
+#[cfg(feature = "vector")]
+use crate::model::{SVector, Vector};
+use crate::{
+    block::{Block, ContinuousState, TransferMut},
+    model::IsValue,
+    solver::{integrate_input, BackwardEuler, Heun, Solver},
+    utils::SimError,
+};
+use std::{fmt::Debug, sync::Arc};
+
+// Value an integrator can hold, seen as its elements.
+pub trait IntegratorState: IsValue + Debug {
+    fn values(&self) -> &[f64];
+
+    fn values_mut(&mut self) -> &mut [f64];
+
+    // Overwrites the elements; storage that can grow takes the new length.
+    fn set_values(&mut self, values: &[f64]) {
+        self.values_mut().copy_from_slice(values);
+    }
+}
+
+impl IntegratorState for f64 {
+    fn values(&self) -> &[f64] {
+        std::slice::from_ref(self)
+    }
+
+    fn values_mut(&mut self) -> &mut [f64] {
+        std::slice::from_mut(self)
+    }
+}
+
+#[cfg(feature = "vector")]
+impl IntegratorState for Vector {
+    fn values(&self) -> &[f64] {
+        self.data()
+    }
+
+    fn values_mut(&mut self) -> &mut [f64] {
+        self.data_mut()
+    }
 
+    fn set_values(&mut self, values: &[f64]) {
+        if self.dim() == values.len() {
+            self.data_mut().copy_from_slice(values);
+        } else {
+            *self = Vector::from(values);
+        }
+    }
+}
+
+#[cfg(feature = "vector")]
+impl<const N: usize> IntegratorState for SVector<N> {
+    fn values(&self) -> &[f64] {
+        self.data()
+    }
+
+    fn values_mut(&mut self) -> &mut [f64] {
+        self.data_mut()
+    }
+}
+
+// The rules picked by `new` are applied in place; any other solver goes
+// through `integrate_input`.
 #[derive(Debug, Clone)]
-pub struct Integrator {
-    init: f64,
-    is_derivative: bool,
+enum Method {
+    Rectangle,
+    Trapezoid,
+    Solver(Arc<dyn Solver>),
+}
+
+static RECTANGLE: BackwardEuler = BackwardEuler::new(1e-10, 50);
+
+#[derive(Debug, Clone)]
+pub struct GenericIntegrator<S> {
+    init: S,
     last_time: f64,
-    last_value: f64,
-    past: f64,
+    last_value: S,
+    past: S,
+    method: Method,
 }
 
-impl Integrator {
-    pub fn new(init: f64, is_derivative: bool) -> Self {
+pub type Integrator = GenericIntegrator<f64>;
+#[cfg(feature = "vector")]
+pub type VectorIntegrator = GenericIntegrator<Vector>;
+// Same as `VectorIntegrator` with the state stored inline.
+#[cfg(feature = "vector")]
+pub type SVectorIntegrator<const N: usize> = GenericIntegrator<SVector<N>>;
+
+impl<S: IntegratorState> GenericIntegrator<S> {
+    // With `is_derivative` each input is the derivative over the step that
+    // ends at its time, so `transfer_mut` adds `input * dt`; otherwise inputs
+    // are integrated with the trapezoidal rule. For the linearly interpolated
+    // input these are exactly the `BackwardEuler` and `Heun` steps.
+    pub fn new(init: impl Into<S>, is_derivative: bool) -> Self {
+        let method = if is_derivative {
+            Method::Rectangle
+        } else {
+            Method::Trapezoid
+        };
+        Self::with_method(init.into(), method)
+    }
+
+    pub fn with_solver(init: impl Into<S>, solver: impl Solver + 'static) -> Self {
+        Self::with_method(init.into(), Method::Solver(Arc::new(solver)))
+    }
+
+    fn with_method(init: S, method: Method) -> Self {
         Self {
+            last_value: init.clone(),
+            past: init.clone(),
             init,
-            last_value: init,
-            past: init,
             last_time: 0.0,
-            is_derivative,
+            method,
         }
     }
 
-    fn integrate(&mut self, t: f64, value: f64) -> f64 {
-        self.past += (t - self.last_time) * (value + self.last_value) * 0.5;
-        self.last_value = value;
+    fn integrate(&mut self, t: f64, value: &S) -> Result<S, SimError> {
+        let h = t - self.last_time;
+        let u0 = self.last_value.values();
+        let u1 = value.values();
+        if u1.len() != self.past.values().len() {
+            return Err(SimError::DimensionMismatch {
+                expected: self.past.values().len(),
+                found: u1.len(),
+            });
+        }
+        match &self.method {
+            Method::Rectangle => {
+                for (x, u1) in self.past.values_mut().iter_mut().zip(u1) {
+                    *x += h * u1;
+                }
+            }
+            Method::Trapezoid => {
+                let past = self.past.values_mut();
+                for ((x, u0), u1) in past.iter_mut().zip(u0).zip(u1) {
+                    *x += 0.5 * h * (u0 + u1);
+                }
+            }
+            Method::Solver(solver) => {
+                let past = integrate_input(
+                    solver.as_ref(),
+                    self.past.values(),
+                    (self.last_time, u0),
+                    (t, u1),
+                )?;
+                self.past.set_values(&past);
+            }
+        }
+        self.last_value.set_values(u1);
         self.last_time = t;
-        self.past
+        Ok(self.past.clone())
     }
 
-    fn derivative_add(&mut self, t: f64, derivative: f64) -> f64 {
-        let delta_t = t - self.last_time;
-        self.past += derivative.clone() * delta_t;
-        self.last_value = derivative;
-        self.last_time = t;
+    pub fn past(&self) -> S {
         self.past.clone()
     }
 
-    pub fn past(&self) -> f64 {
-        self.past
+    pub fn init(&self) -> S {
+        self.init.clone()
     }
 
-    pub fn init(&self) -> f64 {
-        self.init
+    pub fn is_derivative(&self) -> bool {
+        matches!(self.method, Method::Rectangle)
     }
 
-    pub fn is_derivative(&self) -> bool {
-        self.is_derivative
+    pub fn solver(&self) -> &dyn Solver {
+        match &self.method {
+            Method::Rectangle => &RECTANGLE,
+            Method::Trapezoid => &Heun,
+            Method::Solver(solver) => solver.as_ref(),
+        }
+    }
+
+    // Replaces the rule chosen by `new`, so `is_derivative` no longer holds.
+    pub fn set_solver(&mut self, solver: impl Solver + 'static) {
+        self.method = Method::Solver(Arc::new(solver));
     }
 
     pub fn reset(&mut self) {
-        self.last_value = self.init;
-        self.past = self.init;
+        self.last_value = self.init.clone();
+        self.past = self.init.clone();
         self.last_time = 0.0;
     }
 }
 
+// Spelled out per storage, as a generic impl would overlap the blanket one
+// over `Transfer`.
 impl TransferMut<f64, f64> for Integrator {
     fn transfer_mut(&mut self, t: f64, input: &f64) -> f64 {
-        if self.is_derivative {
-            self.derivative_add(t, *input)
-        } else {
-            self.integrate(t, *input)
-        }
+        self.integrate(t, input).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
 #[cfg(feature = "vector")]
-pub(crate) mod vector {
-    use crate::{block::TransferMut, model::Vector};
-
-    #[derive(Debug, Clone)]
-    pub struct VectorIntegrator {
-        init: Vector,
-        last_time: f64,
-        last_value: Vector,
-        past: Vector,
-        is_derivative: bool,
-    }
-
-    impl VectorIntegrator {
-        pub fn new(init: impl Into<Vector>, is_derivative: bool) -> Self {
-            let init = init.into();
-            Self {
-                init: init.clone(),
-                last_value: init.clone(),
-                past: init,
-                last_time: 0.0,
-                is_derivative,
-            }
-        }
+impl TransferMut<Vector, Vector> for VectorIntegrator {
+    fn transfer_mut(&mut self, t: f64, input: &Vector) -> Vector {
+        self.integrate(t, input).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
 
-        fn integrate(&mut self, t: f64, value: &Vector) -> Vector {
-            let value = value.clone();
-            self.past += (value.clone() + self.last_value.clone()) * (t - self.last_time) * 0.5;
-            self.last_value = value;
-            self.last_time = t;
-            self.past.clone()
-        }
+#[cfg(feature = "vector")]
+impl<const N: usize> TransferMut<SVector<N>, SVector<N>> for SVectorIntegrator<N> {
+    fn transfer_mut(&mut self, t: f64, input: &SVector<N>) -> SVector<N> {
+        self.integrate(t, input).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
 
-        fn derivative_add(&mut self, t: f64, derivative: &Vector) -> Vector {
-            let derivative = derivative.clone();
-            let delta_t = t - self.last_time;
-            self.past += derivative.clone() * delta_t;
-            self.last_value = derivative;
-            self.last_time = t;
-            self.past.clone()
-        }
+impl<S: IntegratorState> Block<S> for GenericIntegrator<S> {
+    fn num_inputs(&self) -> usize {
+        1
+    }
 
-        pub fn init(&self) -> Vector {
-            self.init.clone()
-        }
+    fn num_outputs(&self) -> usize {
+        1
+    }
 
-        pub fn is_derivative(&self) -> bool {
-            self.is_derivative
-        }
-        pub fn past(&self) -> Vector {
-            self.past.clone()
-        }
+    fn output(&mut self, _t: f64, _inputs: &[S]) -> Vec<S> {
+        vec![self.past.clone()]
+    }
 
-        pub fn reset(&mut self) {
-            self.last_value = self.init.clone();
-            self.past = self.init.clone();
-            self.last_time = 0.0;
-        }
+    fn direct_feedthrough(&self) -> bool {
+        false
     }
 
-    impl TransferMut<Vector, Vector> for VectorIntegrator {
-        fn transfer_mut(&mut self, t: f64, input: &Vector) -> Vector {
-            if self.is_derivative {
-                self.derivative_add(t, &input)
-            } else {
-                self.integrate(t, &input)
-            }
-        }
+    fn continuous_state(&self) -> Option<&dyn ContinuousState<S>> {
+        Some(self)
+    }
+
+    fn continuous_state_mut(&mut self) -> Option<&mut dyn ContinuousState<S>> {
+        Some(self)
+    }
+
+    fn reset(&mut self) {
+        GenericIntegrator::reset(self)
+    }
+}
+
+impl<S: IntegratorState> ContinuousState<S> for GenericIntegrator<S> {
+    fn state(&self) -> Vec<f64> {
+        self.past.values().to_vec()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        self.past.set_values(state);
+    }
+
+    fn derivative(&self, _t: f64, _x: &[f64], u: &[S]) -> Vec<f64> {
+        u[0].values().to_vec()
+    }
+
+    fn num_states(&self) -> usize {
+        self.past.values().len()
     }
 }
 
 #[cfg(test)]
 mod continuous_tests {
     #[cfg(feature = "vector")]
-    use super::vector::VectorIntegrator;
+    use super::VectorIntegrator;
 
     use super::Integrator;
     use crate::{block::TransferMut, utils::test_logger_init};
-    use log::{info, trace};
-    use std::time::{Duration, SystemTime};
+    use log::trace;
 
     #[test]
     fn test_integrator() {
         test_logger_init();
         let mut i = Integrator::new(0.0, false);
-        let start_time = SystemTime::now();
-        let mut r;
-        loop {
-            let current_time = SystemTime::now();
-            let delta_time = current_time.duration_since(start_time).unwrap();
-            r = i.transfer_mut(delta_time.as_secs_f64(), &delta_time.as_secs_f64());
-            trace!("time: {:?} \n{:?}\n", delta_time, r);
-            if delta_time > Duration::from_secs_f32(1.0) {
-                break;
-            }
+        let mut r = 0.0;
+        for k in 0..=1000 {
+            let t = k as f64 * 1e-3;
+            r = i.transfer_mut(t, &t);
+            trace!("time: {:?} \n{:?}\n", t, r);
         }
-        assert!((r - 0.5).abs() < 1e-5);
+        assert!((r - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_integrator_solver() {
+        use crate::solver::{Euler, Heun, Rk4};
+
+        let mut euler = Integrator::with_solver(0.0, Euler);
+        let mut rk4 = Integrator::with_solver(0.0, Rk4);
+        let mut backward = Integrator::new(0.0, true);
+        let (mut a, mut b, mut c) = (0.0, 0.0, 0.0);
+        for k in 0..=100 {
+            let t = k as f64 * 1e-2;
+            a = euler.transfer_mut(t, &t);
+            b = rk4.transfer_mut(t, &t);
+            c = backward.transfer_mut(t, &t);
+        }
+        assert!((a - 0.495).abs() < 1e-12);
+        assert!((b - 0.5).abs() < 1e-12);
+        assert!((c - 0.505).abs() < 1e-12);
+        assert!(backward.is_derivative() && !rk4.is_derivative());
+        backward.set_solver(Heun);
+        assert!(!backward.is_derivative());
     }
 
     #[cfg(feature = "vector")]
     #[test]
     fn test_vector_integrator() {
-        use crate::{block::TransferMut, model::Vector};
+        use crate::model::Vector;
 
         test_logger_init();
         let mut i = VectorIntegrator::new(vec![0.0, 0.0], false);
-        let start_time = SystemTime::now();
-        let mut r;
-        loop {
-            let current_time = SystemTime::now();
-            let delta_time = current_time.duration_since(start_time).unwrap();
-            r = i.transfer_mut(
-                delta_time.as_secs_f64(),
-                &Vector::from(vec![
-                    delta_time.as_secs_f64(),
-                    2.0 * delta_time.as_secs_f64(),
-                ]),
-            );
-            trace!("time: {:?} \n{:?}\n", delta_time, r);
-            info!("{:?}", i.past());
-            if delta_time > Duration::from_secs_f32(1.0) {
-                break;
+        let mut r = Vector::zero(2);
+        for k in 0..=1000 {
+            let t = k as f64 * 1e-3;
+            r = i.transfer_mut(t, &Vector::from(vec![t, 2.0 * t]));
+            trace!("time: {:?} \n{:?}\n", t, r);
+        }
+        assert!((r[0] - 0.5).abs() < 1e-12);
+        assert!((r[1] - 1.0).abs() < 1e-12);
+    }
+
+    #[cfg(feature = "vector")]
+    #[test]
+    #[should_panic]
+    fn test_vector_integrator_dimension() {
+        use crate::model::Vector;
+
+        let mut i = VectorIntegrator::new(vec![0.0, 0.0], false);
+        i.transfer_mut(1.0, &Vector::from(vec![1.0]));
+    }
+
+    #[cfg(feature = "vector")]
+    #[test]
+    fn test_svector_integrator() {
+        use super::SVectorIntegrator;
+        use crate::{block::Source, diagram::Diagram, model::SVector, simulation::Simulation};
+
+        struct Gravity;
+
+        impl Source<SVector<3>> for Gravity {
+            fn output(&self, _t: f64) -> SVector<3> {
+                SVector::new([0.0, 0.0, -9.81])
             }
         }
 
-        assert!((r[0] - 0.5).abs() < 1e-3);
-        assert!((r[1] - 1.0).abs() < 1e-3);
+        // Free fall from rest, velocity integrated into position.
+        let mut d = Diagram::new();
+        let g = d.add_source("g", Gravity);
+        let v = d.add("v", SVectorIntegrator::new([0.0; 3], false));
+        let p = d.add("p", SVectorIntegrator::new([0.0, 0.0, 100.0], false));
+        d.connect(g, 0, v, 0).unwrap();
+        d.connect(v, 0, p, 0).unwrap();
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.01);
+        let probe = sim.probe(p, 0).unwrap();
+        let p = *sim.run().unwrap().last(probe).unwrap();
+        assert!((p[2] - (100.0 - 9.81 / 2.0)).abs() < 1e-9);
+        assert_eq!((p[0], p[1]), (0.0, 0.0));
     }
 }
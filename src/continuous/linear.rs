@@ -0,0 +1,435 @@
+use crate::{
+    block::{Block, ContinuousState, TransferMut},
+    model::{
+        linear::{affine, check_realization, controllable_canonical},
+        Matrix, Vector,
+    },
+    solver::{integrate_system, Rk4, Solver},
+    utils::SimError,
+};
+use std::sync::Arc;
+
+// dx/dt = A x + B u, y = C x + D u. In a diagram of `f64` values the block has
+// one port per input and output, and a single port otherwise. The state is
+// integrated by the simulation solver; `transfer_mut` integrates it with the
+// block's own solver for standalone use. A vector input whose dimension is
+// not the number of columns of B panics, and `transfer` reports it.
+#[derive(Debug, Clone)]
+pub struct StateSpace {
+    a: Matrix,
+    b: Matrix,
+    c: Matrix,
+    d: Matrix,
+    dims: (usize, usize, usize),
+    init: Vec<f64>,
+    state: Vec<f64>,
+    last_time: f64,
+    last_input: Option<Vec<f64>>,
+    solver: Arc<dyn Solver>,
+}
+
+impl StateSpace {
+    pub fn new(a: Matrix, b: Matrix, c: Matrix, d: Matrix) -> Result<Self, SimError> {
+        let dims = check_realization(&a, &b, &c, &d)?;
+        Ok(Self {
+            a,
+            b,
+            c,
+            d,
+            dims,
+            init: vec![0.0; dims.0],
+            state: vec![0.0; dims.0],
+            last_time: 0.0,
+            last_input: None,
+            solver: Arc::new(Rk4),
+        })
+    }
+
+    pub fn a(&self) -> &Matrix {
+        &self.a
+    }
+
+    pub fn b(&self) -> &Matrix {
+        &self.b
+    }
+
+    pub fn c(&self) -> &Matrix {
+        &self.c
+    }
+
+    pub fn d(&self) -> &Matrix {
+        &self.d
+    }
+
+    pub fn num_states(&self) -> usize {
+        self.dims.0
+    }
+
+    pub fn state(&self) -> &[f64] {
+        &self.state
+    }
+
+    pub fn set_init(&mut self, init: &[f64]) -> Result<(), SimError> {
+        if init.len() != self.dims.0 {
+            return Err(SimError::DimensionMismatch {
+                expected: self.dims.0,
+                found: init.len(),
+            });
+        }
+        self.init = init.to_vec();
+        self.state = init.to_vec();
+        Ok(())
+    }
+
+    pub fn solver(&self) -> &dyn Solver {
+        self.solver.as_ref()
+    }
+
+    pub fn set_solver(&mut self, solver: impl Solver + 'static) {
+        self.solver = Arc::new(solver);
+    }
+
+    pub fn reset(&mut self) {
+        self.state = self.init.clone();
+        self.last_time = 0.0;
+        self.last_input = None;
+    }
+
+    pub fn has_feedthrough(&self) -> bool {
+        self.d.data().iter().any(|v| *v != 0.0)
+    }
+
+    fn check(&self, u: &[f64]) -> Result<(), SimError> {
+        if u.len() != self.dims.1 {
+            return Err(SimError::DimensionMismatch {
+                expected: self.dims.1,
+                found: u.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn derivative_at(&self, x: &[f64], u: &[f64]) -> Vec<f64> {
+        affine(&self.a, x, &self.b, u, self.dims.0)
+    }
+
+    fn output_at(&self, x: &[f64], u: &[f64]) -> Vec<f64> {
+        if self.has_feedthrough() {
+            affine(&self.c, x, &self.d, u, self.dims.2)
+        } else {
+            affine(&self.c, x, &self.d, &[], self.dims.2)
+        }
+    }
+
+    pub fn transfer(&mut self, t: f64, u: &[f64]) -> Result<Vec<f64>, SimError> {
+        self.check(u)?;
+        let last = self.last_input.clone().unwrap_or_else(|| u.to_vec());
+        self.state = integrate_system(
+            self.solver.as_ref(),
+            &self.state,
+            (self.last_time, &last),
+            (t, u),
+            |x, u| self.derivative_at(x, u),
+        )?;
+        self.last_time = t;
+        self.last_input = Some(u.to_vec());
+        Ok(self.output_at(&self.state, u))
+    }
+}
+
+impl TransferMut<f64, f64> for StateSpace {
+    fn transfer_mut(&mut self, t: f64, input: &f64) -> f64 {
+        self.transfer(t, &[*input])
+            .unwrap_or_else(|e| panic!("{}", e))[0]
+    }
+}
+
+impl TransferMut<Vector, Vector> for StateSpace {
+    fn transfer_mut(&mut self, t: f64, input: &Vector) -> Vector {
+        Vector::from(
+            self.transfer(t, input.data())
+                .unwrap_or_else(|e| panic!("{}", e)),
+        )
+    }
+}
+
+impl Block<f64> for StateSpace {
+    fn num_inputs(&self) -> usize {
+        self.dims.1
+    }
+
+    fn num_outputs(&self) -> usize {
+        self.dims.2
+    }
+
+    fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+        self.output_at(&self.state, inputs)
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        self.has_feedthrough()
+    }
+
+    fn continuous_state(&self) -> Option<&dyn ContinuousState<f64>> {
+        Some(self)
+    }
+
+    fn continuous_state_mut(&mut self) -> Option<&mut dyn ContinuousState<f64>> {
+        Some(self)
+    }
+
+    fn reset(&mut self) {
+        StateSpace::reset(self)
+    }
+}
+
+impl ContinuousState<f64> for StateSpace {
+    fn state(&self) -> Vec<f64> {
+        self.state.clone()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        self.state = state.to_vec();
+    }
+
+    fn derivative(&self, _t: f64, x: &[f64], u: &[f64]) -> Vec<f64> {
+        self.derivative_at(x, u)
+    }
+
+    fn num_states(&self) -> usize {
+        self.dims.0
+    }
+}
+
+impl Block<Vector> for StateSpace {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, _t: f64, inputs: &[Vector]) -> Vec<Vector> {
+        // Blocks without feedthrough are evaluated before their input is known.
+        let u = inputs.first().map(|u| u.data()).unwrap_or_default();
+        if !inputs.is_empty() {
+            self.check(u).unwrap_or_else(|e| panic!("{}", e));
+        }
+        vec![Vector::from(self.output_at(&self.state, u))]
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        self.has_feedthrough()
+    }
+
+    fn continuous_state(&self) -> Option<&dyn ContinuousState<Vector>> {
+        Some(self)
+    }
+
+    fn continuous_state_mut(&mut self) -> Option<&mut dyn ContinuousState<Vector>> {
+        Some(self)
+    }
+
+    fn reset(&mut self) {
+        StateSpace::reset(self)
+    }
+}
+
+impl ContinuousState<Vector> for StateSpace {
+    fn state(&self) -> Vec<f64> {
+        self.state.clone()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        self.state = state.to_vec();
+    }
+
+    fn derivative(&self, _t: f64, x: &[f64], u: &[Vector]) -> Vec<f64> {
+        self.check(u[0].data()).unwrap_or_else(|e| panic!("{}", e));
+        self.derivative_at(x, u[0].data())
+    }
+
+    fn num_states(&self) -> usize {
+        self.dims.0
+    }
+}
+
+// Single-input single-output transfer function in s with coefficients in
+// descending powers, realised in controllable canonical form.
+#[derive(Debug, Clone)]
+pub struct TransferFunction {
+    num: Vec<f64>,
+    den: Vec<f64>,
+    realization: StateSpace,
+}
+
+impl TransferFunction {
+    pub fn new(num: Vec<f64>, den: Vec<f64>) -> Result<Self, SimError> {
+        let (a, b, c, d) = controllable_canonical(&num, &den)?;
+        let realization = StateSpace::new(a, b, c, d)?;
+        Ok(Self {
+            num,
+            den,
+            realization,
+        })
+    }
+
+    pub fn num(&self) -> &[f64] {
+        &self.num
+    }
+
+    pub fn den(&self) -> &[f64] {
+        &self.den
+    }
+
+    pub fn realization(&self) -> &StateSpace {
+        &self.realization
+    }
+
+    pub fn realization_mut(&mut self) -> &mut StateSpace {
+        &mut self.realization
+    }
+
+    pub fn reset(&mut self) {
+        self.realization.reset();
+    }
+}
+
+impl From<TransferFunction> for StateSpace {
+    fn from(tf: TransferFunction) -> Self {
+        tf.realization
+    }
+}
+
+impl TransferMut<f64, f64> for TransferFunction {
+    fn transfer_mut(&mut self, t: f64, input: &f64) -> f64 {
+        self.realization.transfer_mut(t, input)
+    }
+}
+
+impl TransferMut<Vector, Vector> for TransferFunction {
+    fn transfer_mut(&mut self, t: f64, input: &Vector) -> Vector {
+        self.realization.transfer_mut(t, input)
+    }
+}
+
+impl Block<f64> for TransferFunction {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        Block::<f64>::output(&mut self.realization, t, inputs)
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        self.realization.has_feedthrough()
+    }
+
+    fn continuous_state(&self) -> Option<&dyn ContinuousState<f64>> {
+        Some(&self.realization)
+    }
+
+    fn continuous_state_mut(&mut self) -> Option<&mut dyn ContinuousState<f64>> {
+        Some(&mut self.realization)
+    }
+
+    fn reset(&mut self) {
+        TransferFunction::reset(self)
+    }
+}
+
+#[cfg(test)]
+mod linear_tests {
+    use super::*;
+    use crate::{
+        block::Source, diagram::Diagram, simulation::Simulation, utils::fixtures::Constant,
+    };
+
+    #[test]
+    fn test_transfer_function() {
+        // 1 / (s + 1) driven by a unit step: y = 1 - e^-t.
+        let mut d = Diagram::new();
+        let unit = d.add_source("unit", Constant(1.0));
+        let lag = d.add(
+            "lag",
+            TransferFunction::new(vec![1.0], vec![1.0, 1.0]).unwrap(),
+        );
+        d.connect(unit, 0, lag, 0).unwrap();
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.01);
+        sim.set_solver(Rk4);
+        let probe = sim.probe(lag, 0).unwrap();
+        let r = sim.run().unwrap();
+        assert!((r.last(probe).unwrap() - (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+
+        // (s + 2) / (s + 1) = 1 + 1 / (s + 1) feeds the input through.
+        let mut lead = TransferFunction::new(vec![1.0, 2.0], vec![1.0, 1.0]).unwrap();
+        assert!(Block::<f64>::direct_feedthrough(&lead));
+        assert_eq!(lead.realization().d()[(0, 0)], 1.0);
+        assert_eq!(lead.transfer_mut(0.0, &1.0), 1.0);
+        let mut y = 0.0;
+        for k in 1..=100 {
+            y = lead.transfer_mut(k as f64 * 0.01, &1.0);
+        }
+        assert!((y - (2.0 - (-1.0f64).exp())).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_state_space() {
+        // Double integrator driven by a unit input, observed as position and
+        // velocity through a single vector port.
+        let ss = StateSpace::new(
+            Matrix::from(vec![vec![0.0, 1.0], vec![0.0, 0.0]]),
+            Matrix::from(vec![vec![0.0], vec![1.0]]),
+            Matrix::from(vec![vec![1.0, 0.0], vec![0.0, 1.0]]),
+            Matrix::from(vec![vec![0.0], vec![0.0]]),
+        )
+        .unwrap();
+        let mut standalone = ss.clone();
+        let mut y = Vector::zero(2);
+        for k in 0..=20 {
+            y = standalone.transfer_mut(k as f64 * 0.1, &Vector::from(vec![1.0]));
+        }
+        assert!((y[0] - 2.0).abs() < 1e-12 && (y[1] - 2.0).abs() < 1e-12);
+
+        struct UnitVector;
+
+        impl Source<Vector> for UnitVector {
+            fn output(&self, _t: f64) -> Vector {
+                Vector::from(vec![1.0])
+            }
+        }
+
+        let mut d = Diagram::new();
+        let unit = d.add_source("unit", UnitVector);
+        let plant = d.add("plant", ss);
+        d.connect(unit, 0, plant, 0).unwrap();
+        let mut sim = Simulation::new(d, 0.0, 2.0, 0.1);
+        sim.set_solver(Rk4);
+        let probe = sim.probe(plant, 0).unwrap();
+        let r = sim.run().unwrap();
+        let y = r.last(probe).unwrap();
+        assert!((y[0] - 2.0).abs() < 1e-12 && (y[1] - 2.0).abs() < 1e-12);
+
+        assert_eq!(
+            standalone.transfer(2.1, &[1.0, 0.0]),
+            Err(SimError::DimensionMismatch {
+                expected: 1,
+                found: 2
+            })
+        );
+
+        assert!(StateSpace::new(
+            Matrix::from(vec![vec![0.0]]),
+            Matrix::from(vec![vec![1.0]]),
+            Matrix::from(vec![vec![1.0, 0.0]]),
+            Matrix::from(vec![vec![0.0]]),
+        )
+        .is_err());
+    }
+}
@@ -1,4 +1,15 @@
 pub(crate) mod differentiator;
 pub(crate) mod integrator;
+#[cfg(feature = "matrix")]
+pub(crate) mod linear;
+pub(crate) mod pid;
 
-pub use integrator::Integrator;
+#[cfg(feature = "vector")]
+pub use differentiator::vector::VectorDifferentiator;
+pub use differentiator::{DerivativeMethod, Differentiator};
+pub use integrator::{GenericIntegrator, Integrator, IntegratorState};
+#[cfg(feature = "vector")]
+pub use integrator::{SVectorIntegrator, VectorIntegrator};
+#[cfg(feature = "matrix")]
+pub use linear::{StateSpace, TransferFunction};
+pub use pid::{AntiWindup, Pid, PidForm};
@@ -0,0 +1,492 @@
+use crate::{
+    block::{Block, ContinuousState, SampleTime},
+    utils::SimError,
+};
+
+// Parallel: u = Kp e + Ki ∫e + Kd de/dt.
+// Ideal: u = Kp (e + Ki ∫e + Kd de/dt), with Ki = 1/Ti and Kd = Td.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PidForm {
+    Parallel,
+    Ideal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AntiWindup {
+    None,
+    // Feeds `Kb (u_sat - u)` back into the integrator.
+    BackCalculation(f64),
+    // Stops integrating while the output is saturated and the error would
+    // push it further into saturation.
+    Clamping,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Signals {
+    error: f64,
+    derivative_error: f64,
+    unsaturated: f64,
+    output: f64,
+}
+
+// PID controller with the setpoint on input 0 and the measurement on input 1.
+// The derivative is filtered as `N s / (s + N)` and the proportional and
+// derivative errors use the setpoint weights `b` and `c`. With tracking
+// enabled, input 2 is the signal the output should follow, which gives a
+// bumpless transfer when switching back from manual control.
+//
+// `new` builds a continuous controller whose integrator and filter states are
+// integrated by the simulation solver. `discrete` builds one updated at the
+// sample hits, with a forward Euler integrator and a backward Euler filter.
+#[derive(Debug, Clone)]
+pub struct Pid {
+    kp: f64,
+    ki: f64,
+    kd: f64,
+    form: PidForm,
+    filter: f64,
+    lower: f64,
+    upper: f64,
+    anti_windup: AntiWindup,
+    weights: (f64, f64),
+    tracking: Option<f64>,
+    init: (f64, f64),
+    integral: f64,
+    filter_state: f64,
+    last: Option<f64>,
+    sample_time: SampleTime,
+    resolved: SampleTime,
+}
+
+impl Pid {
+    pub fn new(kp: f64, ki: f64, kd: f64) -> Self {
+        Self::with_sample_time(kp, ki, kd, SampleTime::Continuous)
+    }
+
+    pub fn discrete(kp: f64, ki: f64, kd: f64, sample_time: SampleTime) -> Self {
+        Self::with_sample_time(kp, ki, kd, sample_time)
+    }
+
+    fn with_sample_time(kp: f64, ki: f64, kd: f64, sample_time: SampleTime) -> Self {
+        Self {
+            kp,
+            ki,
+            kd,
+            form: PidForm::Parallel,
+            filter: 100.0,
+            lower: f64::NEG_INFINITY,
+            upper: f64::INFINITY,
+            anti_windup: AntiWindup::None,
+            weights: (1.0, 1.0),
+            tracking: None,
+            init: (0.0, 0.0),
+            integral: 0.0,
+            filter_state: 0.0,
+            last: None,
+            sample_time,
+            resolved: sample_time,
+        }
+    }
+
+    pub fn gains(&self) -> (f64, f64, f64) {
+        (self.kp, self.ki, self.kd)
+    }
+
+    pub fn form(&self) -> PidForm {
+        self.form
+    }
+
+    pub fn set_form(&mut self, form: PidForm) {
+        self.form = form;
+    }
+
+    pub fn filter(&self) -> f64 {
+        self.filter
+    }
+
+    // An infinite coefficient gives an unfiltered backward difference and is
+    // only accepted by discrete controllers.
+    pub fn set_filter(&mut self, n: f64) -> Result<(), SimError> {
+        if n.is_nan() || n <= 0.0 || (n.is_infinite() && !self.is_discrete()) {
+            return Err(SimError::InvalidParameter(format!(
+                "derivative filter coefficient must be positive and finite, got {n}"
+            )));
+        }
+        self.filter = n;
+        Ok(())
+    }
+
+    pub fn limits(&self) -> (f64, f64) {
+        (self.lower, self.upper)
+    }
+
+    pub fn set_limits(&mut self, lower: f64, upper: f64) -> Result<(), SimError> {
+        if lower.is_nan() || upper.is_nan() || lower > upper {
+            return Err(SimError::InvalidParameter(format!(
+                "invalid output limits [{lower}, {upper}]"
+            )));
+        }
+        self.lower = lower;
+        self.upper = upper;
+        Ok(())
+    }
+
+    pub fn anti_windup(&self) -> AntiWindup {
+        self.anti_windup
+    }
+
+    pub fn set_anti_windup(&mut self, anti_windup: AntiWindup) -> Result<(), SimError> {
+        if let AntiWindup::BackCalculation(kb) = anti_windup {
+            if kb.is_nan() || kb <= 0.0 {
+                return Err(SimError::InvalidParameter(format!(
+                    "back-calculation gain must be positive, got {kb}"
+                )));
+            }
+        }
+        self.anti_windup = anti_windup;
+        Ok(())
+    }
+
+    pub fn setpoint_weights(&self) -> (f64, f64) {
+        self.weights
+    }
+
+    pub fn set_setpoint_weights(&mut self, b: f64, c: f64) {
+        self.weights = (b, c);
+    }
+
+    pub fn tracking(&self) -> Option<f64> {
+        self.tracking
+    }
+
+    // Enables tracking with gain `kt`, adding the tracking input.
+    pub fn set_tracking(&mut self, kt: Option<f64>) -> Result<(), SimError> {
+        if let Some(kt) = kt {
+            if kt.is_nan() || kt <= 0.0 {
+                return Err(SimError::InvalidParameter(format!(
+                    "tracking gain must be positive, got {kt}"
+                )));
+            }
+        }
+        self.tracking = kt;
+        Ok(())
+    }
+
+    // Initial integral term and derivative filter state.
+    pub fn set_init(&mut self, integral: f64, filter: f64) {
+        self.init = (integral, filter);
+        self.integral = integral;
+        self.filter_state = filter;
+    }
+
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    // Sampled and held when the sample time, as resolved by the diagram for an
+    // inherited controller, is discrete or triggered.
+    pub fn is_discrete(&self) -> bool {
+        matches!(
+            self.resolved,
+            SampleTime::Discrete { .. } | SampleTime::Triggered(_)
+        )
+    }
+
+    pub fn reset(&mut self) {
+        (self.integral, self.filter_state) = self.init;
+        self.last = None;
+    }
+
+    // Proportional, integral and derivative gains of the parallel form.
+    fn parallel_gains(&self) -> (f64, f64, f64) {
+        match self.form {
+            PidForm::Parallel => (self.kp, self.ki, self.kd),
+            PidForm::Ideal => (self.kp, self.kp * self.ki, self.kp * self.kd),
+        }
+    }
+
+    fn step(&self, t: f64) -> f64 {
+        match (self.resolved, self.last) {
+            (SampleTime::Discrete { period, .. }, _) => period,
+            (_, Some(last)) => t - last,
+            (_, None) => 0.0,
+        }
+    }
+
+    // Gain applied to `e_d - filter` to form the filtered derivative.
+    fn filter_gain(&self, t: f64) -> f64 {
+        if !self.is_discrete() {
+            return self.filter;
+        }
+        let h = self.step(t);
+        match (self.filter.is_infinite(), h > 0.0) {
+            (true, true) => 1.0 / h,
+            (true, false) => 0.0,
+            (false, _) => self.filter / (1.0 + h * self.filter),
+        }
+    }
+
+    fn signals(&self, t: f64, integral: f64, filter: f64, inputs: &[f64]) -> Signals {
+        let (kp, _, kd) = self.parallel_gains();
+        let (b, c) = self.weights;
+        let (r, y) = (inputs[0], inputs[1]);
+        let error = r - y;
+        let derivative_error = c * r - y;
+        let unsaturated =
+            kp * (b * r - y) + integral + kd * self.filter_gain(t) * (derivative_error - filter);
+        Signals {
+            error,
+            derivative_error,
+            unsaturated,
+            output: unsaturated.clamp(self.lower, self.upper),
+        }
+    }
+
+    fn integral_rate(&self, s: &Signals, inputs: &[f64]) -> f64 {
+        let rate = self.parallel_gains().1 * s.error;
+        let rate = match self.anti_windup {
+            AntiWindup::None => rate,
+            AntiWindup::BackCalculation(kb) => rate + kb * (s.output - s.unsaturated),
+            AntiWindup::Clamping => {
+                let saturated = (s.unsaturated > s.output && rate > 0.0)
+                    || (s.unsaturated < s.output && rate < 0.0);
+                if saturated {
+                    0.0
+                } else {
+                    rate
+                }
+            }
+        };
+        match self.tracking {
+            Some(kt) => rate + kt * (inputs[2] - s.output),
+            None => rate,
+        }
+    }
+}
+
+impl Block<f64> for Pid {
+    fn num_inputs(&self) -> usize {
+        if self.tracking.is_some() {
+            3
+        } else {
+            2
+        }
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        vec![
+            self.signals(t, self.integral, self.filter_state, inputs)
+                .output,
+        ]
+    }
+
+    fn update(&mut self, t: f64, inputs: &[f64]) {
+        if !self.is_discrete() {
+            return;
+        }
+        let s = self.signals(t, self.integral, self.filter_state, inputs);
+        let h = self.step(t);
+        self.integral += h * self.integral_rate(&s, inputs);
+        self.filter_state = if self.filter.is_infinite() {
+            s.derivative_error
+        } else {
+            (self.filter_state + h * self.filter * s.derivative_error) / (1.0 + h * self.filter)
+        };
+        self.last = Some(t);
+    }
+
+    // Locates the instants the output enters or leaves saturation.
+    fn zero_crossings(&self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        if self.is_discrete() {
+            return Vec::new();
+        }
+        let u = self
+            .signals(t, self.integral, self.filter_state, inputs)
+            .unsaturated;
+        [(self.lower, u - self.lower), (self.upper, self.upper - u)]
+            .into_iter()
+            .filter(|(limit, _)| limit.is_finite())
+            .map(|(_, z)| z)
+            .collect()
+    }
+
+    fn sample_time(&self) -> SampleTime {
+        self.sample_time
+    }
+
+    fn inherit_sample_time(&mut self, sample_time: SampleTime) {
+        self.resolved = sample_time;
+    }
+
+    fn continuous_state(&self) -> Option<&dyn ContinuousState<f64>> {
+        if self.is_discrete() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+
+    fn continuous_state_mut(&mut self) -> Option<&mut dyn ContinuousState<f64>> {
+        if self.is_discrete() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+
+    fn reset(&mut self) {
+        Pid::reset(self)
+    }
+}
+
+impl ContinuousState<f64> for Pid {
+    fn state(&self) -> Vec<f64> {
+        vec![self.integral, self.filter_state]
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        self.integral = state[0];
+        self.filter_state = state[1];
+    }
+
+    fn derivative(&self, t: f64, x: &[f64], u: &[f64]) -> Vec<f64> {
+        let s = self.signals(t, x[0], x[1], u);
+        vec![
+            self.integral_rate(&s, u),
+            self.filter * (s.derivative_error - x[1]),
+        ]
+    }
+
+    fn num_states(&self) -> usize {
+        2
+    }
+}
+
+#[cfg(test)]
+mod pid_tests {
+    use super::*;
+
+    fn run(pid: &mut Pid, ts: f64, inputs: &[[f64; 2]]) -> Vec<f64> {
+        inputs
+            .iter()
+            .enumerate()
+            .map(|(k, u)| {
+                let t = k as f64 * ts;
+                let y = pid.output(t, u)[0];
+                pid.update(t, u);
+                y
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_discrete() {
+        let ts = SampleTime::discrete(0.5);
+        let e = [[1.0, 0.0]; 4];
+        let mut pi = Pid::discrete(2.0, 1.0, 0.0, ts);
+        assert_eq!(run(&mut pi, 0.5, &e), vec![2.0, 2.5, 3.0, 3.5]);
+        let mut ideal = Pid::discrete(2.0, 1.0, 0.0, ts);
+        ideal.set_form(PidForm::Ideal);
+        assert_eq!(run(&mut ideal, 0.5, &e), vec![2.0, 3.0, 4.0, 5.0]);
+
+        // An unfiltered derivative is a backward difference of the error.
+        let mut pd = Pid::discrete(0.0, 0.0, 1.0, ts);
+        pd.set_filter(f64::INFINITY).unwrap();
+        let ramp = [[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [2.0, 0.0]];
+        assert_eq!(run(&mut pd, 0.5, &ramp), vec![0.0, 2.0, 2.0, 0.0]);
+
+        // A weight of zero keeps setpoint steps out of the P and D terms.
+        let mut weighted = Pid::discrete(1.0, 0.0, 1.0, ts);
+        weighted.set_filter(f64::INFINITY).unwrap();
+        weighted.set_setpoint_weights(0.0, 0.0);
+        assert_eq!(
+            run(&mut weighted, 0.5, &[[0.0, 0.0], [1.0, 0.0]]),
+            [0.0, 0.0]
+        );
+        assert!(Pid::new(1.0, 0.0, 1.0).set_filter(f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn test_anti_windup() {
+        let ts = SampleTime::discrete(1.0);
+        let e = [[1.0, 0.0]; 6];
+        let mut plain = Pid::discrete(0.0, 1.0, 0.0, ts);
+        plain.set_limits(-2.0, 2.0).unwrap();
+        assert_eq!(run(&mut plain, 1.0, &e), vec![0.0, 1.0, 2.0, 2.0, 2.0, 2.0]);
+        assert_eq!(plain.integral(), 6.0);
+
+        let mut clamped = plain.clone();
+        clamped.reset();
+        clamped.set_anti_windup(AntiWindup::Clamping).unwrap();
+        run(&mut clamped, 1.0, &e);
+        assert_eq!(clamped.integral(), 3.0);
+        // The integrator unwinds as soon as the error changes sign.
+        assert_eq!(run(&mut clamped, 1.0, &[[-1.0, 0.0]; 2]), vec![2.0, 2.0]);
+        assert_eq!(clamped.integral(), 1.0);
+
+        let mut back = plain.clone();
+        back.reset();
+        back.set_anti_windup(AntiWindup::BackCalculation(1.0))
+            .unwrap();
+        run(&mut back, 1.0, &e);
+        // Back-calculation settles where Ki e + Kb (u_sat - u) = 0.
+        assert_eq!(back.integral(), 3.0);
+        assert!(back
+            .set_anti_windup(AntiWindup::BackCalculation(0.0))
+            .is_err());
+    }
+
+    #[test]
+    fn test_continuous() {
+        use crate::{
+            diagram::Diagram,
+            simulation::Simulation,
+            solver::Rk4,
+            utils::fixtures::{Constant, Lag},
+        };
+
+        // PI control of dy/dt = u - y, closed through a first-order plant
+        // without direct feedthrough so the loop has no algebraic path.
+        let control = |mut pid: Pid| {
+            pid.set_limits(-5.0, 5.0).unwrap();
+            pid.set_anti_windup(AntiWindup::Clamping).unwrap();
+            let mut d = Diagram::new();
+            let setpoint = d.add_source("setpoint", Constant(1.0));
+            let pid = d.add("pid", pid);
+            let plant = d.add("plant", Lag(0.0));
+            d.connect(setpoint, 0, pid, 0).unwrap();
+            d.connect(plant, 0, pid, 1).unwrap();
+            d.connect(pid, 0, plant, 0).unwrap();
+            let mut sim = Simulation::new(d, 0.0, 20.0, 0.01);
+            sim.set_solver(Rk4);
+            let y = sim.probe(plant, 0).unwrap();
+            let r = sim.run().unwrap();
+            let discrete = sim.diagram().get::<Pid>(pid).unwrap().is_discrete();
+            (*r.last(y).unwrap(), discrete)
+        };
+        let (y, discrete) = control(Pid::new(2.0, 2.0, 0.1));
+        // The integral action removes the steady-state error.
+        assert!((y - 1.0).abs() < 1e-6);
+        assert!(!discrete);
+
+        // Inherited from continuous drivers, the controller runs continuously.
+        let inherited = Pid::discrete(2.0, 2.0, 0.1, SampleTime::Inherited);
+        assert_eq!(control(inherited), (y, false));
+
+        // Tracking pulls the integral term so the output follows input 2.
+        let mut tracking = Pid::new(1.0, 1.0, 0.0);
+        tracking.set_tracking(Some(10.0)).unwrap();
+        assert_eq!(tracking.num_inputs(), 3);
+        let mut x = vec![0.0, 0.0];
+        for _ in 0..1000 {
+            let dx = tracking.derivative(0.0, &x, &[1.0, 1.0, 3.0]);
+            x[0] += 0.001 * dx[0];
+        }
+        tracking.set_state(&x);
+        assert!((tracking.output(0.0, &[1.0, 1.0, 3.0])[0] - 3.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{
+    block::{Block, SampleTime, SinkMut, Source, SourceMut, Transfer, TransferMut},
+    model::IsValue,
+};
+
+#[derive(Debug, Clone)]
+pub struct SourceBlock<S> {
+    source: S,
+}
+
+impl<S> SourceBlock<S> {
+    pub fn new(source: S) -> Self {
+        Self { source }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.source
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+}
+
+impl<S, V> Block<V> for SourceBlock<S>
+where
+    S: Source<V>,
+    V: IsValue,
+{
+    fn num_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, _inputs: &[V]) -> Vec<V> {
+        vec![self.source.output(t)]
+    }
+
+    fn zero_crossings(&self, t: f64, _inputs: &[V]) -> Vec<f64> {
+        Source::zero_crossings(&self.source, t)
+    }
+}
+
+// Advances a stateful source once per major step. Solver stages and event
+// probes in between see the output of the last major step, so the source
+// only ever moves forward in time.
+#[derive(Debug, Clone)]
+pub struct SourceMutBlock<S> {
+    source: S,
+    initial: S,
+}
+
+impl<S: Clone> SourceMutBlock<S> {
+    pub fn new(source: S) -> Self {
+        Self {
+            initial: source.clone(),
+            source,
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.source
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+}
+
+impl<S, V> Block<V> for SourceMutBlock<S>
+where
+    S: SourceMut<V> + Clone,
+    V: IsValue,
+{
+    fn num_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, _inputs: &[V]) -> Vec<V> {
+        vec![self.source.output_mut(t)]
+    }
+
+    fn zero_crossings(&self, t: f64, _inputs: &[V]) -> Vec<f64> {
+        SourceMut::zero_crossings(&self.source, t)
+    }
+
+    fn reset(&mut self) {
+        self.source = self.initial.clone();
+    }
+
+    fn sample_time(&self) -> SampleTime {
+        SampleTime::FixedInMinorStep
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferBlock<T> {
+    transfer: T,
+}
+
+impl<T> TransferBlock<T> {
+    pub fn new(transfer: T) -> Self {
+        Self { transfer }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.transfer
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.transfer
+    }
+}
+
+impl<T, V> Block<V> for TransferBlock<T>
+where
+    T: Transfer<V, V>,
+    V: IsValue,
+{
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, inputs: &[V]) -> Vec<V> {
+        vec![self.transfer.transfer(t, &inputs[0])]
+    }
+
+    fn zero_crossings(&self, t: f64, inputs: &[V]) -> Vec<f64> {
+        Transfer::zero_crossings(&self.transfer, t, &inputs[0])
+    }
+}
+
+// Advances a stateful transfer once per major step, holding its output
+// through the solver stages and event probes in between.
+#[derive(Debug, Clone)]
+pub struct TransferMutBlock<T> {
+    transfer: T,
+    initial: T,
+}
+
+impl<T: Clone> TransferMutBlock<T> {
+    pub fn new(transfer: T) -> Self {
+        Self {
+            initial: transfer.clone(),
+            transfer,
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.transfer
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.transfer
+    }
+}
+
+impl<T, V> Block<V> for TransferMutBlock<T>
+where
+    T: TransferMut<V, V> + Clone,
+    V: IsValue,
+{
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, inputs: &[V]) -> Vec<V> {
+        vec![self.transfer.transfer_mut(t, &inputs[0])]
+    }
+
+    fn zero_crossings(&self, t: f64, inputs: &[V]) -> Vec<f64> {
+        TransferMut::zero_crossings(&self.transfer, t, &inputs[0])
+    }
+
+    fn reset(&mut self) {
+        self.transfer = self.initial.clone();
+    }
+
+    fn sample_time(&self) -> SampleTime {
+        SampleTime::FixedInMinorStep
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SinkBlock<K> {
+    sink: K,
+}
+
+impl<K> SinkBlock<K> {
+    pub fn new(sink: K) -> Self {
+        Self { sink }
+    }
+
+    pub fn inner(&self) -> &K {
+        &self.sink
+    }
+
+    pub fn inner_mut(&mut self) -> &mut K {
+        &mut self.sink
+    }
+}
+
+impl<K, V> Block<V> for SinkBlock<K>
+where
+    K: SinkMut<V>,
+    V: IsValue,
+{
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        0
+    }
+
+    fn output(&mut self, _t: f64, _inputs: &[V]) -> Vec<V> {
+        Vec::new()
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        false
+    }
+
+    fn update(&mut self, t: f64, inputs: &[V]) {
+        self.sink.input_mut(t, &inputs[0]);
+    }
+
+    fn reset(&mut self) {
+        self.sink.reset_mut();
+    }
+}
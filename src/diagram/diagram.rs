@@ -0,0 +1,803 @@
+use super::adapter::{SinkBlock, SourceBlock, SourceMutBlock, TransferBlock, TransferMutBlock};
+use crate::{
+    block::{period_gcd, Block, SampleTime, SinkMut, Source, SourceMut, Transfer, TransferMut},
+    model::IsValue,
+    utils::SimError,
+};
+use std::{any::Any, collections::VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId(usize);
+
+impl BlockId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+pub(crate) trait AnyBlock<V: IsValue>: Block<V> {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<B, V> AnyBlock<V> for B
+where
+    B: Block<V> + 'static,
+    V: IsValue,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+pub(crate) struct Node<V: IsValue> {
+    pub(crate) name: String,
+    pub(crate) block: Box<dyn AnyBlock<V>>,
+    pub(crate) inputs: Vec<Option<(BlockId, usize)>>,
+    pub(crate) outputs: Vec<V>,
+    sample_time: SampleTime,
+    crossings: Vec<f64>,
+    fired: bool,
+}
+
+pub struct Diagram<V: IsValue> {
+    pub(crate) nodes: Vec<Node<V>>,
+    pub(crate) order: Option<Vec<BlockId>>,
+    time: Option<f64>,
+}
+
+impl<V: IsValue + 'static> Diagram<V> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            order: None,
+            time: None,
+        }
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, block: impl Block<V> + 'static) -> BlockId {
+        let inputs = vec![None; block.num_inputs()];
+        self.nodes.push(Node {
+            name: name.into(),
+            block: Box::new(block),
+            inputs,
+            outputs: Vec::new(),
+            sample_time: SampleTime::Continuous,
+            crossings: Vec::new(),
+            fired: false,
+        });
+        self.order = None;
+        BlockId(self.nodes.len() - 1)
+    }
+
+    pub fn add_source<S>(&mut self, name: impl Into<String>, source: S) -> BlockId
+    where
+        S: Source<V> + 'static,
+    {
+        self.add(name, SourceBlock::new(source))
+    }
+
+    // Stateful sources are advanced once per major step and held in between.
+    pub fn add_source_mut<S>(&mut self, name: impl Into<String>, source: S) -> BlockId
+    where
+        S: SourceMut<V> + Clone + 'static,
+    {
+        self.add(name, SourceMutBlock::new(source))
+    }
+
+    pub fn add_transfer<T>(&mut self, name: impl Into<String>, transfer: T) -> BlockId
+    where
+        T: Transfer<V, V> + 'static,
+    {
+        self.add(name, TransferBlock::new(transfer))
+    }
+
+    // Stateful transfers are advanced once per major step and held in between.
+    pub fn add_transfer_mut<T>(&mut self, name: impl Into<String>, transfer: T) -> BlockId
+    where
+        T: TransferMut<V, V> + Clone + 'static,
+    {
+        self.add(name, TransferMutBlock::new(transfer))
+    }
+
+    pub fn add_sink<K>(&mut self, name: impl Into<String>, sink: K) -> BlockId
+    where
+        K: SinkMut<V> + 'static,
+    {
+        self.add(name, SinkBlock::new(sink))
+    }
+}
+
+impl<V: IsValue + 'static> Default for Diagram<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: IsValue> Diagram<V> {
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn name(&self, id: BlockId) -> Option<&str> {
+        self.nodes.get(id.0).map(|n| n.name.as_str())
+    }
+
+    pub fn find(&self, name: &str) -> Option<BlockId> {
+        self.nodes.iter().position(|n| n.name == name).map(BlockId)
+    }
+
+    pub fn get<B: 'static>(&self, id: BlockId) -> Option<&B> {
+        self.nodes.get(id.0)?.block.as_any().downcast_ref()
+    }
+
+    pub fn get_mut<B: 'static>(&mut self, id: BlockId) -> Option<&mut B> {
+        self.nodes.get_mut(id.0)?.block.as_any_mut().downcast_mut()
+    }
+
+    pub fn connect(
+        &mut self,
+        from: BlockId,
+        out_port: usize,
+        to: BlockId,
+        in_port: usize,
+    ) -> Result<(), SimError> {
+        let source = self.node(from)?;
+        let outputs = source.block.num_outputs();
+        if out_port >= outputs {
+            return Err(SimError::PortOutOfRange {
+                block: source.name.clone(),
+                port: out_port,
+                ports: outputs,
+            });
+        }
+
+        let target = self.node(to)?;
+        if in_port >= target.inputs.len() {
+            return Err(SimError::PortOutOfRange {
+                block: target.name.clone(),
+                port: in_port,
+                ports: target.inputs.len(),
+            });
+        }
+        if target.inputs[in_port].is_some() {
+            return Err(SimError::InputAlreadyConnected {
+                block: target.name.clone(),
+                port: in_port,
+            });
+        }
+
+        self.nodes[to.0].inputs[in_port] = Some((from, out_port));
+        self.order = None;
+        Ok(())
+    }
+
+    pub fn disconnect(&mut self, to: BlockId, in_port: usize) -> Result<(), SimError> {
+        let target = self.node(to)?;
+        if in_port >= target.inputs.len() {
+            return Err(SimError::PortOutOfRange {
+                block: target.name.clone(),
+                port: in_port,
+                ports: target.inputs.len(),
+            });
+        }
+        self.nodes[to.0].inputs[in_port] = None;
+        self.order = None;
+        Ok(())
+    }
+
+    pub fn compile(&mut self) -> Result<&[BlockId], SimError> {
+        if self.order.is_none() {
+            self.order = Some(self.schedule()?);
+            self.resolve_sample_times();
+        }
+        Ok(self.order.as_deref().unwrap_or_default())
+    }
+
+    pub fn order(&self) -> Option<&[BlockId]> {
+        self.order.as_deref()
+    }
+
+    // Sample time after inherited rates are resolved by `compile`.
+    pub fn sample_time(&self, id: BlockId) -> Option<SampleTime> {
+        self.order.as_ref()?;
+        self.nodes.get(id.0).map(|n| n.sample_time)
+    }
+
+    // Greatest common divisor of the discrete periods and offsets, so every
+    // discrete hit falls on a multiple of it.
+    pub fn base_rate(&self) -> Option<f64> {
+        self.order.as_ref()?;
+        self.nodes
+            .iter()
+            .filter_map(|n| match n.sample_time {
+                SampleTime::Discrete { period, offset } => Some([period, offset]),
+                _ => None,
+            })
+            .flatten()
+            .filter(|p| *p > 0.0)
+            .reduce(period_gcd)
+    }
+
+    // Continuous states are advanced with forward Euler from the previous
+    // step; use `Simulation` for a proper solver.
+    pub fn step(&mut self, t: f64) -> Result<(), SimError> {
+        self.compile()?;
+        if let Some(last) = self.time.filter(|last| t > *last) {
+            let x = self.states();
+            let dx = self.derivatives(last, &x);
+            let h = t - last;
+            let x = x
+                .iter()
+                .zip(dx.iter())
+                .map(|(x, dx)| x + h * dx)
+                .collect::<Vec<_>>();
+            self.load_states(&x);
+        }
+        self.evaluate_outputs(t, true);
+        self.update(t);
+        self.time = Some(t);
+        Ok(())
+    }
+
+    pub fn output(&self, id: BlockId, port: usize) -> Option<&V> {
+        self.nodes.get(id.0)?.outputs.get(port)
+    }
+
+    pub fn outputs(&self, id: BlockId) -> Option<&[V]> {
+        self.nodes.get(id.0).map(|n| n.outputs.as_slice())
+    }
+
+    fn node(&self, id: BlockId) -> Result<&Node<V>, SimError> {
+        self.nodes.get(id.0).ok_or(SimError::UnknownBlock(id.0))
+    }
+
+    pub(crate) fn gather_inputs(&self, id: BlockId) -> Vec<V> {
+        self.nodes[id.0]
+            .inputs
+            .iter()
+            .flatten()
+            .map(|(from, port)| self.nodes[from.0].outputs[*port].clone())
+            .collect()
+    }
+
+    // Outside major steps, and in major steps between their hits, discrete,
+    // triggered and fixed in minor step blocks with direct feedthrough keep
+    // the outputs of their last hit.
+    pub(crate) fn evaluate_outputs(&mut self, t: f64, major: bool) {
+        let order = self.order.take().unwrap_or_default();
+        for id in order.iter() {
+            let sample_time = self.nodes[id.0].sample_time;
+            let hit = match sample_time {
+                SampleTime::Triggered(edge) if major => {
+                    let inputs = self.gather_inputs(*id);
+                    let node = &mut self.nodes[id.0];
+                    let crossings = node.block.zero_crossings(t, &inputs);
+                    node.fired = edge.fired(&node.crossings, &crossings);
+                    node.crossings = crossings;
+                    node.fired
+                }
+                _ => major && sample_time.is_hit(t),
+            };
+            let node = &self.nodes[id.0];
+            if sample_time.holds_output()
+                && node.block.direct_feedthrough()
+                && !node.outputs.is_empty()
+                && !hit
+            {
+                continue;
+            }
+            let inputs = if node.block.direct_feedthrough() {
+                self.gather_inputs(*id)
+            } else {
+                Vec::new()
+            };
+            let node = &mut self.nodes[id.0];
+            node.outputs = node.block.output(t, &inputs);
+        }
+        self.order = Some(order);
+    }
+
+    // Resets every block and forgets the outputs and step time of the last
+    // run.
+    pub fn reset(&mut self) {
+        for node in self.nodes.iter_mut() {
+            node.block.reset();
+            node.outputs.clear();
+            node.crossings.clear();
+            node.fired = false;
+        }
+        self.time = None;
+    }
+
+    pub(crate) fn update(&mut self, t: f64) {
+        for i in 0..self.nodes.len() {
+            let hit = match self.nodes[i].sample_time {
+                SampleTime::Triggered(_) => self.nodes[i].fired,
+                sample_time => sample_time.is_hit(t),
+            };
+            if !hit {
+                continue;
+            }
+            let inputs = self.gather_inputs(BlockId(i));
+            self.nodes[i].block.update(t, &inputs);
+        }
+    }
+
+    pub fn states(&self) -> Vec<f64> {
+        self.nodes
+            .iter()
+            .filter_map(|n| n.block.continuous_state())
+            .flat_map(|c| c.state())
+            .collect()
+    }
+
+    pub fn set_states(&mut self, x: &[f64]) -> Result<(), SimError> {
+        let expected = self.num_states();
+        if x.len() != expected {
+            return Err(SimError::DimensionMismatch {
+                expected,
+                found: x.len(),
+            });
+        }
+        self.load_states(x);
+        Ok(())
+    }
+
+    pub fn num_states(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter_map(|n| n.block.continuous_state())
+            .map(|c| c.num_states())
+            .sum()
+    }
+
+    // `set_states` for vectors the engine built from `states` itself.
+    pub(crate) fn load_states(&mut self, x: &[f64]) {
+        let mut offset = 0;
+        for c in self
+            .nodes
+            .iter_mut()
+            .filter_map(|n| n.block.continuous_state_mut())
+        {
+            let len = c.num_states();
+            c.set_state(&x[offset..offset + len]);
+            offset += len;
+        }
+    }
+
+    // Derivatives at `x` given the outputs currently held by the diagram.
+    pub(crate) fn derivatives(&self, t: f64, x: &[f64]) -> Vec<f64> {
+        let mut dx = Vec::with_capacity(x.len());
+        for (i, node) in self.nodes.iter().enumerate() {
+            if let Some(c) = node.block.continuous_state() {
+                let inputs = self.gather_inputs(BlockId(i));
+                let len = c.num_states();
+                dx.extend(c.derivative(t, &x[dx.len()..dx.len() + len], &inputs));
+            }
+        }
+        dx
+    }
+
+    // Name of the first block, in insertion order, whose outputs or
+    // continuous states hold a NaN.
+    pub(crate) fn find_nan(&self) -> Option<&str> {
+        self.nodes
+            .iter()
+            .find(|n| {
+                n.outputs.iter().any(|o| o.has_nan())
+                    || n.block
+                        .continuous_state()
+                        .is_some_and(|c| c.state().iter().any(|x| x.is_nan()))
+            })
+            .map(|n| n.name.as_str())
+    }
+
+    pub(crate) fn next_hit(&self, t: f64) -> Option<f64> {
+        self.nodes
+            .iter()
+            .filter_map(|n| n.sample_time.next_hit(t))
+            .min_by(|a, b| a.total_cmp(b))
+    }
+
+    pub(crate) fn zero_crossings(&self, t: f64) -> Vec<f64> {
+        (0..self.nodes.len())
+            .flat_map(|i| {
+                let inputs = self.gather_inputs(BlockId(i));
+                self.nodes[i].block.zero_crossings(t, &inputs)
+            })
+            .collect()
+    }
+
+    // Inherited blocks take the rate of the blocks driving them: continuous if
+    // any driver is, triggered if any driver is, otherwise the greatest common
+    // divisor of the driving periods. Each inherited block is recomputed from
+    // all of its resolved drivers until nothing changes, so the result does not
+    // depend on the order blocks were added. Blocks left unresolved, such as
+    // inherited sources, run continuously.
+    fn resolve_sample_times(&mut self) {
+        let mut resolved = self
+            .nodes
+            .iter()
+            .map(|n| Some(n.block.sample_time()).filter(|s| *s != SampleTime::Inherited))
+            .collect::<Vec<_>>();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (i, node) in self.nodes.iter().enumerate() {
+                if node.block.sample_time() != SampleTime::Inherited {
+                    continue;
+                }
+                let sample_time = node
+                    .inputs
+                    .iter()
+                    .flatten()
+                    .filter_map(|(from, _)| resolved[from.0])
+                    .reduce(combine);
+                if sample_time != resolved[i] {
+                    resolved[i] = sample_time;
+                    changed = true;
+                }
+            }
+        }
+        for (node, resolved) in self.nodes.iter_mut().zip(resolved) {
+            let sample_time = resolved.unwrap_or(SampleTime::Continuous);
+            if node.block.sample_time() == SampleTime::Inherited {
+                node.block.inherit_sample_time(sample_time);
+            }
+            node.sample_time = sample_time;
+            node.crossings.clear();
+            node.fired = false;
+        }
+    }
+
+    fn schedule(&self) -> Result<Vec<BlockId>, SimError> {
+        for node in self.nodes.iter() {
+            if let Some(port) = node.inputs.iter().position(|i| i.is_none()) {
+                return Err(SimError::UnconnectedInput {
+                    block: node.name.clone(),
+                    port,
+                });
+            }
+            if let SampleTime::Discrete { period, offset } = node.block.sample_time() {
+                if !(period > 0.0 && period.is_finite() && offset.is_finite()) {
+                    return Err(SimError::InvalidParameter(format!(
+                        "block {} has sample period {} and offset {}",
+                        node.name, period, offset
+                    )));
+                }
+            }
+        }
+
+        let n = self.nodes.len();
+        let mut in_degree = vec![0usize; n];
+        let mut successors = vec![Vec::new(); n];
+        for (to, node) in self.nodes.iter().enumerate() {
+            if !node.block.direct_feedthrough() {
+                continue;
+            }
+            for (from, _) in node.inputs.iter().flatten() {
+                in_degree[to] += 1;
+                successors[from.0].push(to);
+            }
+        }
+
+        let mut ready = (0..n)
+            .filter(|i| in_degree[*i] == 0)
+            .collect::<VecDeque<_>>();
+        let mut order = Vec::with_capacity(n);
+        while let Some(i) = ready.pop_front() {
+            order.push(BlockId(i));
+            for j in successors[i].iter() {
+                in_degree[*j] -= 1;
+                if in_degree[*j] == 0 {
+                    ready.push_back(*j);
+                }
+            }
+        }
+
+        if order.len() < n {
+            let start = (0..n).find(|i| in_degree[*i] > 0).unwrap_or_default();
+            return Err(SimError::AlgebraicLoop(self.find_loop(start, &in_degree)));
+        }
+        Ok(order)
+    }
+
+    // Every unscheduled block has a feedthrough input from another unscheduled
+    // block, so walking those inputs backwards must eventually revisit a block.
+    fn find_loop(&self, start: usize, in_degree: &[usize]) -> Vec<String> {
+        let mut path = vec![start];
+        loop {
+            let current = *path.last().unwrap_or(&start);
+            let next = self.nodes[current]
+                .inputs
+                .iter()
+                .flatten()
+                .map(|(from, _)| from.0)
+                .find(|from| in_degree[*from] > 0)
+                .unwrap_or(current);
+            if let Some(pos) = path.iter().position(|i| *i == next) {
+                return path[pos..]
+                    .iter()
+                    .rev()
+                    .map(|i| self.nodes[*i].name.clone())
+                    .collect();
+            }
+            path.push(next);
+        }
+    }
+}
+
+fn combine(a: SampleTime, b: SampleTime) -> SampleTime {
+    match (a, b) {
+        // Blocks driven by held outputs still run at every stage.
+        (SampleTime::Continuous | SampleTime::FixedInMinorStep, _)
+        | (_, SampleTime::Continuous | SampleTime::FixedInMinorStep) => SampleTime::Continuous,
+        (SampleTime::Triggered(edge), _) | (_, SampleTime::Triggered(edge)) => {
+            SampleTime::Triggered(edge)
+        }
+        (
+            SampleTime::Discrete { period, offset },
+            SampleTime::Discrete {
+                period: other_period,
+                offset: other_offset,
+            },
+        ) => {
+            if period == other_period && offset == other_offset {
+                a
+            } else {
+                let period = [other_period, offset, other_offset]
+                    .into_iter()
+                    .filter(|p| *p > 0.0)
+                    .fold(period, period_gcd);
+                SampleTime::discrete(period)
+            }
+        }
+        _ => a,
+    }
+}
+
+#[cfg(test)]
+mod diagram_tests {
+    use super::*;
+    use crate::{
+        block::Sink,
+        utils::fixtures::{Gain, Ramp},
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    struct Sum;
+
+    impl Block<f64> for Sum {
+        fn num_inputs(&self) -> usize {
+            2
+        }
+
+        fn num_outputs(&self) -> usize {
+            1
+        }
+
+        fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+            vec![inputs[0] + inputs[1]]
+        }
+    }
+
+    struct Probe(Rc<RefCell<Vec<(f64, f64)>>>);
+
+    impl Sink<f64> for Probe {
+        fn input(&self, t: f64, value: &f64) {
+            self.0.borrow_mut().push((t, *value));
+        }
+    }
+
+    #[test]
+    fn test_step_in_order() {
+        let record = Rc::new(RefCell::new(Vec::new()));
+        let mut d = Diagram::new();
+        let probe = d.add_sink("probe", Probe(record.clone()));
+        let sum = d.add("sum", Sum);
+        let gain = d.add_transfer("gain", Gain(2.0));
+        let ramp = d.add_source("ramp", Ramp);
+        d.connect(ramp, 0, gain, 0).unwrap();
+        d.connect(ramp, 0, sum, 0).unwrap();
+        d.connect(gain, 0, sum, 1).unwrap();
+        d.connect(sum, 0, probe, 0).unwrap();
+
+        let order = d.compile().unwrap().to_vec();
+        let pos = |id: BlockId| order.iter().position(|x| *x == id).unwrap();
+        assert!(pos(ramp) < pos(gain));
+        assert!(pos(gain) < pos(sum));
+
+        for i in 0..3 {
+            d.step(i as f64).unwrap();
+        }
+        assert_eq!(*record.borrow(), vec![(0.0, 0.0), (1.0, 3.0), (2.0, 6.0)]);
+        assert_eq!(d.output(sum, 0), Some(&6.0));
+    }
+
+    #[test]
+    fn test_connect_errors() {
+        let mut d = Diagram::<f64>::new();
+        let ramp = d.add_source("ramp", Ramp);
+        let gain = d.add_transfer("gain", Gain(1.0));
+        assert!(matches!(
+            d.connect(ramp, 1, gain, 0),
+            Err(SimError::PortOutOfRange { .. })
+        ));
+        d.connect(ramp, 0, gain, 0).unwrap();
+        assert!(matches!(
+            d.connect(ramp, 0, gain, 0),
+            Err(SimError::InputAlreadyConnected { .. })
+        ));
+
+        let sum = d.add("sum", Sum);
+        d.connect(gain, 0, sum, 0).unwrap();
+        assert_eq!(
+            d.compile().unwrap_err(),
+            SimError::UnconnectedInput {
+                block: "sum".to_string(),
+                port: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_algebraic_loop() {
+        let mut d = Diagram::<f64>::new();
+        let ramp = d.add_source("ramp", Ramp);
+        let sum = d.add("sum", Sum);
+        let a = d.add_transfer("a", Gain(1.0));
+        let b = d.add_transfer("b", Gain(1.0));
+        d.connect(ramp, 0, sum, 0).unwrap();
+        d.connect(b, 0, sum, 1).unwrap();
+        d.connect(sum, 0, a, 0).unwrap();
+        d.connect(a, 0, b, 0).unwrap();
+        match d.step(0.0) {
+            Err(SimError::AlgebraicLoop(chain)) => {
+                assert_eq!(chain.len(), 3);
+                let start = chain.iter().position(|n| n == "sum").unwrap();
+                let rotated = [&chain[start..], &chain[..start]].concat();
+                assert_eq!(rotated, vec!["sum", "a", "b"]);
+            }
+            other => panic!("expected algebraic loop, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "continuous")]
+    #[test]
+    fn test_integrator_breaks_loop() {
+        use crate::continuous::Integrator;
+
+        let mut d = Diagram::<f64>::new();
+        let integrator = d.add("integrator", Integrator::new(1.0, true));
+        let gain = d.add_transfer("gain", Gain(-1.0));
+        d.connect(integrator, 0, gain, 0).unwrap();
+        d.connect(gain, 0, integrator, 0).unwrap();
+
+        let dt = 1e-3;
+        for i in 0..=1000 {
+            d.step(i as f64 * dt).unwrap();
+        }
+        let x = *d.output(integrator, 0).unwrap();
+        assert!((x - (-1.0f64).exp()).abs() < 1e-3);
+
+        assert_eq!(d.num_states(), 1);
+        d.set_states(&[2.0]).unwrap();
+        assert_eq!(d.states(), vec![2.0]);
+        assert_eq!(
+            d.set_states(&[1.0, 2.0]),
+            Err(SimError::DimensionMismatch {
+                expected: 1,
+                found: 2
+            })
+        );
+    }
+
+    struct Rate(SampleTime);
+
+    impl Block<f64> for Rate {
+        fn num_inputs(&self) -> usize {
+            1
+        }
+
+        fn num_outputs(&self) -> usize {
+            1
+        }
+
+        fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+            vec![inputs[0]]
+        }
+
+        fn sample_time(&self) -> SampleTime {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_sample_times() {
+        let mut d = Diagram::new();
+        let ramp = d.add_source("ramp", Ramp);
+        let sensor = d.add("sensor", Rate(SampleTime::discrete(0.01)));
+        let controller = d.add("controller", Rate(SampleTime::discrete(0.02)));
+        let sum = d.add("sum", Sum);
+        let plant = d.add("plant", Rate(SampleTime::Inherited));
+        let filter = d.add("filter", Rate(SampleTime::Inherited));
+        d.connect(ramp, 0, sensor, 0).unwrap();
+        d.connect(sensor, 0, controller, 0).unwrap();
+        d.connect(sensor, 0, sum, 0).unwrap();
+        d.connect(controller, 0, sum, 1).unwrap();
+        d.connect(controller, 0, filter, 0).unwrap();
+        d.connect(ramp, 0, plant, 0).unwrap();
+        assert_eq!(d.sample_time(filter), None);
+        d.compile().unwrap();
+
+        assert_eq!(d.sample_time(filter), Some(SampleTime::discrete(0.02)));
+        assert_eq!(d.sample_time(plant), Some(SampleTime::Continuous));
+        assert_eq!(d.sample_time(sum), Some(SampleTime::Continuous));
+        assert!((d.base_rate().unwrap() - 0.01).abs() < 1e-15);
+        assert!((period_gcd(0.3, 0.12) - 0.06).abs() < 1e-12);
+
+        // Between the controller hits its held output lags the sensor.
+        d.step(0.0).unwrap();
+        d.step(0.01).unwrap();
+        assert_eq!(d.output(sensor, 0), Some(&0.01));
+        assert_eq!(d.output(controller, 0), Some(&0.0));
+        d.step(0.02).unwrap();
+        assert_eq!(d.output(filter, 0), Some(&0.02));
+
+        // A block fed by a discrete block and by an inherited chain that only
+        // resolves later is continuous whichever order the blocks are added.
+        struct Join;
+
+        impl Block<f64> for Join {
+            fn num_inputs(&self) -> usize {
+                2
+            }
+
+            fn num_outputs(&self) -> usize {
+                1
+            }
+
+            fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+                vec![inputs[0] + inputs[1]]
+            }
+
+            fn sample_time(&self) -> SampleTime {
+                SampleTime::Inherited
+            }
+        }
+
+        for join_first in [true, false] {
+            let mut d = Diagram::new();
+            let ramp = d.add_source("ramp", Ramp);
+            let sensor = d.add("sensor", Rate(SampleTime::discrete(0.1)));
+            let join = join_first.then(|| d.add("join", Join));
+            let tail = d.add("tail", Rate(SampleTime::Inherited));
+            let head = d.add("head", Rate(SampleTime::Inherited));
+            let join = join.unwrap_or_else(|| d.add("join", Join));
+            d.connect(ramp, 0, sensor, 0).unwrap();
+            d.connect(ramp, 0, head, 0).unwrap();
+            d.connect(head, 0, tail, 0).unwrap();
+            d.connect(sensor, 0, join, 0).unwrap();
+            d.connect(tail, 0, join, 1).unwrap();
+            d.compile().unwrap();
+            assert_eq!(d.sample_time(join), Some(SampleTime::Continuous));
+        }
+
+        for period in [0.0, -0.1, f64::NAN] {
+            let mut d = Diagram::new();
+            let ramp = d.add_source("ramp", Ramp);
+            let rate = d.add("rate", Rate(SampleTime::discrete(period)));
+            d.connect(ramp, 0, rate, 0).unwrap();
+            assert!(matches!(d.compile(), Err(SimError::InvalidParameter(_))));
+        }
+    }
+}
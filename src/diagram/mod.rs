@@ -0,0 +1,6 @@
+pub(crate) mod adapter;
+#[allow(clippy::module_inception)]
+pub(crate) mod diagram;
+
+pub use adapter::*;
+pub use diagram::{BlockId, Diagram};
@@ -1,4 +1,8 @@
-use crate::block::Transfer;
+use crate::{
+    block::{Block, Transfer},
+    utils::SimError,
+};
+use std::f64::consts::PI;
 
 #[derive(Debug, Clone)]
 pub struct Saturation {
@@ -7,15 +11,20 @@ pub struct Saturation {
 }
 
 impl Saturation {
-    pub fn new(top: f64, bottom: f64) -> Self {
-        Self { top, bottom }
+    pub fn new(top: f64, bottom: f64) -> Result<Self, SimError> {
+        if top.is_nan() || bottom.is_nan() || bottom > top {
+            return Err(SimError::InvalidParameter(format!(
+                "invalid saturation limits [{bottom}, {top}]"
+            )));
+        }
+        Ok(Self { top, bottom })
     }
 
-    fn saturation(&self, x: f64) -> f64 {
+    pub(crate) fn saturation(&self, x: f64) -> f64 {
         if x > self.top {
-            x
+            self.top
         } else if x < self.bottom {
-            x
+            self.bottom
         } else {
             x
         }
@@ -34,4 +43,232 @@ impl Transfer<f64, f64> for Saturation {
     fn transfer(&self, _t: f64, input: &f64) -> f64 {
         self.saturation(*input)
     }
+
+    fn zero_crossings(&self, _t: f64, input: &f64) -> Vec<f64> {
+        vec![*input - self.top, *input - self.bottom]
+    }
+}
+
+impl Block<f64> for Saturation {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+        vec![self.saturation(inputs[0])]
+    }
+
+    fn zero_crossings(&self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        Transfer::zero_crossings(self, t, &inputs[0])
+    }
+}
+
+// Zero between `start` and `end`, and the input shifted by the nearest edge
+// outside of it.
+#[derive(Debug, Clone)]
+pub struct DeadZone {
+    start: f64,
+    end: f64,
+}
+
+impl DeadZone {
+    pub fn new(start: f64, end: f64) -> Result<Self, SimError> {
+        if start.is_nan() || end.is_nan() || start > end {
+            return Err(SimError::InvalidParameter(format!(
+                "invalid dead zone [{start}, {end}]"
+            )));
+        }
+        Ok(Self { start, end })
+    }
+
+    pub fn start(&self) -> f64 {
+        self.start
+    }
+
+    pub fn end(&self) -> f64 {
+        self.end
+    }
+
+    fn dead_zone(&self, x: f64) -> f64 {
+        if x > self.end {
+            x - self.end
+        } else if x < self.start {
+            x - self.start
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Transfer<f64, f64> for DeadZone {
+    fn transfer(&self, _t: f64, input: &f64) -> f64 {
+        self.dead_zone(*input)
+    }
+
+    fn zero_crossings(&self, _t: f64, input: &f64) -> Vec<f64> {
+        vec![*input - self.start, *input - self.end]
+    }
+}
+
+impl Block<f64> for DeadZone {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+        vec![self.dead_zone(inputs[0])]
+    }
+
+    fn zero_crossings(&self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        Transfer::zero_crossings(self, t, &inputs[0])
+    }
+}
+
+// Rounds the input to the nearest multiple of `interval`.
+#[derive(Debug, Clone)]
+pub struct Quantizer {
+    interval: f64,
+}
+
+impl Quantizer {
+    pub fn new(interval: f64) -> Result<Self, SimError> {
+        if !(interval > 0.0 && interval.is_finite()) {
+            return Err(SimError::InvalidParameter(format!(
+                "quantization interval must be positive, got {interval}"
+            )));
+        }
+        Ok(Self { interval })
+    }
+
+    pub fn interval(&self) -> f64 {
+        self.interval
+    }
+
+    fn quantize(&self, x: f64) -> f64 {
+        self.interval * (x / self.interval).round()
+    }
+}
+
+impl Transfer<f64, f64> for Quantizer {
+    fn transfer(&self, _t: f64, input: &f64) -> f64 {
+        self.quantize(*input)
+    }
+
+    // Changes sign at every step of the output, half an interval away from
+    // each level.
+    fn zero_crossings(&self, _t: f64, input: &f64) -> Vec<f64> {
+        vec![(PI * *input / self.interval).cos()]
+    }
+}
+
+impl Block<f64> for Quantizer {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+        vec![self.quantize(inputs[0])]
+    }
+
+    fn zero_crossings(&self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        Transfer::zero_crossings(self, t, &inputs[0])
+    }
+}
+
+// y = Fc sign(x) + Fv x, with sign(0) = 0.
+#[derive(Debug, Clone)]
+pub struct CoulombViscousFriction {
+    coulomb: f64,
+    viscous: f64,
+}
+
+impl CoulombViscousFriction {
+    pub fn new(coulomb: f64, viscous: f64) -> Self {
+        Self { coulomb, viscous }
+    }
+
+    pub fn coulomb(&self) -> f64 {
+        self.coulomb
+    }
+
+    pub fn viscous(&self) -> f64 {
+        self.viscous
+    }
+
+    fn friction(&self, x: f64) -> f64 {
+        let sign = if x == 0.0 { 0.0 } else { x.signum() };
+        self.coulomb * sign + self.viscous * x
+    }
+}
+
+impl Transfer<f64, f64> for CoulombViscousFriction {
+    fn transfer(&self, _t: f64, input: &f64) -> f64 {
+        self.friction(*input)
+    }
+
+    fn zero_crossings(&self, _t: f64, input: &f64) -> Vec<f64> {
+        vec![*input]
+    }
+}
+
+impl Block<f64> for CoulombViscousFriction {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+        vec![self.friction(inputs[0])]
+    }
+
+    fn zero_crossings(&self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        Transfer::zero_crossings(self, t, &inputs[0])
+    }
+}
+
+#[cfg(test)]
+mod discontinuous_tests {
+    use super::*;
+
+    fn apply(b: &impl Transfer<f64, f64>, xs: &[f64]) -> Vec<f64> {
+        xs.iter().map(|x| b.transfer(0.0, x)).collect()
+    }
+
+    #[test]
+    fn test_static() {
+        let xs = [-3.0, -0.5, 0.0, 0.5, 3.0];
+        let sat = Saturation::new(1.0, -2.0).unwrap();
+        assert!(Saturation::new(-2.0, 1.0).is_err());
+        assert!(Saturation::new(f64::NAN, 1.0).is_err());
+        assert_eq!(apply(&sat, &xs), vec![-2.0, -0.5, 0.0, 0.5, 1.0]);
+        let dz = DeadZone::new(-1.0, 1.0).unwrap();
+        assert_eq!(apply(&dz, &xs), vec![-2.0, 0.0, 0.0, 0.0, 2.0]);
+        assert!(DeadZone::new(1.0, -1.0).is_err());
+        let q = Quantizer::new(0.5).unwrap();
+        assert_eq!(
+            apply(&q, &[-0.3, 0.2, 0.74, 1.3]),
+            vec![-0.5, 0.0, 0.5, 1.5]
+        );
+        assert!(Quantizer::new(0.0).is_err());
+        let g0 = Block::zero_crossings(&q, 0.0, &[0.24]);
+        let g1 = Block::zero_crossings(&q, 0.0, &[0.26]);
+        assert!(g0[0] * g1[0] < 0.0);
+        let friction = CoulombViscousFriction::new(1.0, 2.0);
+        assert_eq!(apply(&friction, &xs), vec![-7.0, -2.0, 0.0, 2.0, 7.0]);
+    }
 }
@@ -0,0 +1,417 @@
+use crate::{
+    block::{Block, TransferMut, TriggerEdge},
+    utils::SimError,
+};
+
+// The output follows the input once it has moved across a gap of `width`
+// centred on the output, and holds otherwise. In a diagram the blocks in this
+// file only change state in `update`, so solver stages see the last accepted
+// one.
+#[derive(Debug, Clone)]
+pub struct Backlash {
+    width: f64,
+    init: f64,
+    state: f64,
+}
+
+impl Backlash {
+    pub fn new(width: f64, init: f64) -> Result<Self, SimError> {
+        if !(width >= 0.0 && width.is_finite()) {
+            return Err(SimError::InvalidParameter(format!(
+                "backlash width must be non-negative, got {width}"
+            )));
+        }
+        Ok(Self {
+            width,
+            init,
+            state: init,
+        })
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn state(&self) -> f64 {
+        self.state
+    }
+
+    pub fn reset(&mut self) {
+        self.state = self.init;
+    }
+
+    fn backlash(&self, x: f64) -> f64 {
+        let half = 0.5 * self.width;
+        if x > self.state + half {
+            x - half
+        } else if x < self.state - half {
+            x + half
+        } else {
+            self.state
+        }
+    }
+}
+
+impl TransferMut<f64, f64> for Backlash {
+    fn transfer_mut(&mut self, _t: f64, input: &f64) -> f64 {
+        self.state = self.backlash(*input);
+        self.state
+    }
+
+    fn zero_crossings(&self, _t: f64, input: &f64) -> Vec<f64> {
+        let half = 0.5 * self.width;
+        vec![*input - self.state - half, *input - self.state + half]
+    }
+}
+
+impl Block<f64> for Backlash {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+        vec![self.backlash(inputs[0])]
+    }
+
+    fn update(&mut self, _t: f64, inputs: &[f64]) {
+        self.state = self.backlash(inputs[0]);
+    }
+
+    fn zero_crossings(&self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        TransferMut::zero_crossings(self, t, &inputs[0])
+    }
+
+    fn reset(&mut self) {
+        Backlash::reset(self)
+    }
+}
+
+// Limits the slope of the output to `falling <= dy/dt <= rising`. The first
+// sample passes through, and a repeated or earlier time holds the last output.
+// The output is continuous, so there are no zero crossings to locate.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    rising: f64,
+    falling: f64,
+    last: Option<(f64, f64)>,
+}
+
+impl RateLimiter {
+    pub fn new(rising: f64, falling: f64) -> Result<Self, SimError> {
+        if rising.is_nan() || falling.is_nan() || rising < 0.0 || falling > 0.0 {
+            return Err(SimError::InvalidParameter(format!(
+                "rate limits must satisfy falling <= 0 <= rising, got [{falling}, {rising}]"
+            )));
+        }
+        Ok(Self {
+            rising,
+            falling,
+            last: None,
+        })
+    }
+
+    pub fn rising(&self) -> f64 {
+        self.rising
+    }
+
+    pub fn falling(&self) -> f64 {
+        self.falling
+    }
+
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    fn limit(&self, t: f64, x: f64) -> f64 {
+        match self.last {
+            Some((last, y)) if t > last => {
+                let dt = t - last;
+                y + (x - y).clamp(self.falling * dt, self.rising * dt)
+            }
+            Some((_, y)) => y,
+            None => x,
+        }
+    }
+
+    fn advance(&mut self, t: f64, x: f64) -> f64 {
+        let y = self.limit(t, x);
+        if self.last.is_none_or(|(last, _)| t > last) {
+            self.last = Some((t, y));
+        }
+        y
+    }
+}
+
+impl TransferMut<f64, f64> for RateLimiter {
+    fn transfer_mut(&mut self, t: f64, input: &f64) -> f64 {
+        self.advance(t, *input)
+    }
+}
+
+impl Block<f64> for RateLimiter {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        vec![self.limit(t, inputs[0])]
+    }
+
+    fn update(&mut self, t: f64, inputs: &[f64]) {
+        self.advance(t, inputs[0]);
+    }
+
+    fn reset(&mut self) {
+        RateLimiter::reset(self)
+    }
+}
+
+// Switches on when the input reaches `on_point` and off when it falls to
+// `off_point`, holding its state in between.
+#[derive(Debug, Clone)]
+pub struct Relay {
+    on_point: f64,
+    off_point: f64,
+    on_value: f64,
+    off_value: f64,
+    on: bool,
+}
+
+impl Relay {
+    pub fn new(
+        on_point: f64,
+        off_point: f64,
+        on_value: f64,
+        off_value: f64,
+    ) -> Result<Self, SimError> {
+        if on_point.is_nan() || off_point.is_nan() || off_point > on_point {
+            return Err(SimError::InvalidParameter(format!(
+                "relay off point {off_point} is above its on point {on_point}"
+            )));
+        }
+        Ok(Self {
+            on_point,
+            off_point,
+            on_value,
+            off_value,
+            on: false,
+        })
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    pub fn reset(&mut self) {
+        self.on = false;
+    }
+
+    fn switch(&self, x: f64) -> bool {
+        if x >= self.on_point {
+            true
+        } else if x <= self.off_point {
+            false
+        } else {
+            self.on
+        }
+    }
+
+    fn value(&self, on: bool) -> f64 {
+        if on {
+            self.on_value
+        } else {
+            self.off_value
+        }
+    }
+}
+
+impl TransferMut<f64, f64> for Relay {
+    fn transfer_mut(&mut self, _t: f64, input: &f64) -> f64 {
+        self.on = self.switch(*input);
+        self.value(self.on)
+    }
+
+    fn zero_crossings(&self, _t: f64, input: &f64) -> Vec<f64> {
+        vec![*input - self.on_point, *input - self.off_point]
+    }
+}
+
+impl Block<f64> for Relay {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+        vec![self.value(self.switch(inputs[0]))]
+    }
+
+    fn update(&mut self, _t: f64, inputs: &[f64]) {
+        self.on = self.switch(inputs[0]);
+    }
+
+    fn zero_crossings(&self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        TransferMut::zero_crossings(self, t, &inputs[0])
+    }
+
+    fn reset(&mut self) {
+        Relay::reset(self)
+    }
+}
+
+// Outputs 1 on the first accepted step at which the input has crossed
+// `offset` in the given direction, and 0 otherwise. The zero crossing makes
+// the simulation step onto the crossing.
+#[derive(Debug, Clone)]
+pub struct HitCrossing {
+    offset: f64,
+    direction: TriggerEdge,
+    last: Option<f64>,
+}
+
+impl HitCrossing {
+    pub fn new(offset: f64, direction: TriggerEdge) -> Self {
+        Self {
+            offset,
+            direction,
+            last: None,
+        }
+    }
+
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    pub fn direction(&self) -> TriggerEdge {
+        self.direction
+    }
+
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    fn hit(&self, x: f64) -> f64 {
+        match self.last {
+            Some(last) if self.direction.fired(&[last], &[x - self.offset]) => 1.0,
+            _ => 0.0,
+        }
+    }
+}
+
+impl TransferMut<f64, f64> for HitCrossing {
+    fn transfer_mut(&mut self, _t: f64, input: &f64) -> f64 {
+        let y = self.hit(*input);
+        self.last = Some(input - self.offset);
+        y
+    }
+
+    fn zero_crossings(&self, _t: f64, input: &f64) -> Vec<f64> {
+        vec![*input - self.offset]
+    }
+}
+
+impl Block<f64> for HitCrossing {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+        vec![self.hit(inputs[0])]
+    }
+
+    fn update(&mut self, _t: f64, inputs: &[f64]) {
+        self.last = Some(inputs[0] - self.offset);
+    }
+
+    fn zero_crossings(&self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        TransferMut::zero_crossings(self, t, &inputs[0])
+    }
+
+    fn reset(&mut self) {
+        HitCrossing::reset(self)
+    }
+}
+
+#[cfg(test)]
+mod dynamic_tests {
+    use super::*;
+
+    fn run(b: &mut impl TransferMut<f64, f64>, xs: &[f64]) -> Vec<f64> {
+        xs.iter()
+            .enumerate()
+            .map(|(k, x)| b.transfer_mut(k as f64, x))
+            .collect()
+    }
+
+    #[test]
+    fn test_memory() {
+        let mut backlash = Backlash::new(1.0, 0.0).unwrap();
+        let xs = [0.4, 1.5, 1.0, 0.5, -1.0];
+        assert_eq!(run(&mut backlash, &xs), vec![0.0, 1.0, 1.0, 1.0, -0.5]);
+        assert!(Backlash::new(-1.0, 0.0).is_err());
+
+        let mut limiter = RateLimiter::new(1.0, -0.5).unwrap();
+        let xs = [2.0, 5.0, 5.0, 0.0];
+        assert_eq!(run(&mut limiter, &xs), vec![2.0, 3.0, 4.0, 3.5]);
+        assert!(RateLimiter::new(-1.0, 0.0).is_err());
+
+        // Repeated and earlier times hold the output, also for unbounded rates.
+        let mut limiter = RateLimiter::new(1.0, -0.5).unwrap();
+        assert_eq!(limiter.transfer_mut(1.0, &0.0), 0.0);
+        assert_eq!(limiter.transfer_mut(0.5, &5.0), 0.0);
+        assert_eq!(limiter.transfer_mut(1.5, &5.0), 0.5);
+        let mut limiter = RateLimiter::new(f64::INFINITY, f64::NEG_INFINITY).unwrap();
+        assert_eq!(limiter.transfer_mut(1.0, &2.0), 2.0);
+        assert_eq!(limiter.transfer_mut(1.0, &3.0), 2.0);
+        assert_eq!(limiter.transfer_mut(2.0, &-3.0), -3.0);
+
+        let mut relay = Relay::new(1.0, -1.0, 5.0, -5.0).unwrap();
+        let xs = [0.0, 1.0, 0.0, -0.5, -1.0, 0.5];
+        assert_eq!(run(&mut relay, &xs), vec![-5.0, 5.0, 5.0, 5.0, -5.0, -5.0]);
+        assert!(Relay::new(-1.0, 1.0, 1.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_hit_crossing() {
+        use crate::{diagram::Diagram, simulation::Simulation, utils::fixtures::Ramp};
+
+        let mut rising = HitCrossing::new(1.0, TriggerEdge::Rising);
+        let xs = [0.0, 1.0, 2.0, 0.0, 1.5];
+        assert_eq!(run(&mut rising, &xs), vec![0.0, 1.0, 0.0, 0.0, 1.0]);
+        let mut either = HitCrossing::new(1.0, TriggerEdge::Either);
+        assert_eq!(run(&mut either, &xs), vec![0.0, 1.0, 0.0, 1.0, 1.0]);
+
+        // The crossing at t = 0.35 is off the grid, so the engine steps onto it.
+        let mut d = Diagram::new();
+        let ramp = d.add_source("ramp", Ramp);
+        let hit = d.add("hit", HitCrossing::new(0.35, TriggerEdge::Rising));
+        d.connect(ramp, 0, hit, 0).unwrap();
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.1);
+        let probe = sim.probe(hit, 0).unwrap();
+        let r = sim.run().unwrap();
+        let hits = r
+            .times()
+            .iter()
+            .zip(r.signal(probe).unwrap())
+            .filter(|(_, y)| **y == 1.0)
+            .map(|(t, _)| *t)
+            .collect::<Vec<_>>();
+        assert_eq!(hits.len(), 1);
+        assert!((hits[0] - 0.35).abs() < 1e-9);
+    }
+}
@@ -1,2 +1,10 @@
+#[allow(clippy::module_inception)]
 pub(crate) mod discontinuous;
-pub use discontinuous::Saturation;
+pub(crate) mod dynamic;
+#[cfg(feature = "vector")]
+pub(crate) mod vector;
+
+pub use discontinuous::{CoulombViscousFriction, DeadZone, Quantizer, Saturation};
+pub use dynamic::{Backlash, HitCrossing, RateLimiter, Relay};
+#[cfg(feature = "vector")]
+pub use vector::{Elementwise, VectorLimits, VectorSaturation};
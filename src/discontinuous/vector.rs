@@ -0,0 +1,324 @@
+use crate::{
+    block::{Block, Transfer},
+    discontinuous::Saturation,
+    model::Vector,
+    utils::SimError,
+};
+
+// Applies one scalar block per element of a single vector port, so each
+// element keeps its own parameters and state. An input whose dimension is not
+// the number of channels panics.
+#[derive(Debug, Clone)]
+pub struct Elementwise<B> {
+    channels: Vec<B>,
+}
+
+impl<B: Block<f64>> Elementwise<B> {
+    pub fn new(channels: Vec<B>) -> Self {
+        Self { channels }
+    }
+
+    pub fn repeat(block: B, dim: usize) -> Self
+    where
+        B: Clone,
+    {
+        Self::new(vec![block; dim])
+    }
+
+    pub fn channels(&self) -> &[B] {
+        &self.channels
+    }
+
+    pub fn channels_mut(&mut self) -> &mut [B] {
+        &mut self.channels
+    }
+}
+
+impl<B> Elementwise<B> {
+    fn check(&self, input: &Vector) -> Result<(), SimError> {
+        if input.dim() != self.channels.len() {
+            return Err(SimError::DimensionMismatch {
+                expected: self.channels.len(),
+                found: input.dim(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<B: Transfer<f64, f64>> Transfer<Vector, Vector> for Elementwise<B> {
+    fn transfer(&self, t: f64, input: &Vector) -> Vector {
+        self.check(input).unwrap_or_else(|e| panic!("{}", e));
+        Vector::from(
+            self.channels
+                .iter()
+                .zip(input.data())
+                .map(|(b, x)| b.transfer(t, x))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn zero_crossings(&self, t: f64, input: &Vector) -> Vec<f64> {
+        self.check(input).unwrap_or_else(|e| panic!("{}", e));
+        self.channels
+            .iter()
+            .zip(input.data())
+            .flat_map(|(b, x)| Transfer::zero_crossings(b, t, x))
+            .collect()
+    }
+}
+
+impl<B: Block<f64>> Block<Vector> for Elementwise<B> {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, inputs: &[Vector]) -> Vec<Vector> {
+        // Without direct feedthrough the input is not known yet.
+        let y = match inputs.first() {
+            Some(input) => {
+                self.check(input).unwrap_or_else(|e| panic!("{}", e));
+                self.channels
+                    .iter_mut()
+                    .zip(input.data())
+                    .map(|(b, x)| b.output(t, &[*x])[0])
+                    .collect::<Vec<_>>()
+            }
+            None => self
+                .channels
+                .iter_mut()
+                .map(|b| b.output(t, &[])[0])
+                .collect(),
+        };
+        vec![Vector::from(y)]
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        self.channels.iter().any(|b| b.direct_feedthrough())
+    }
+
+    fn update(&mut self, t: f64, inputs: &[Vector]) {
+        self.check(&inputs[0]).unwrap_or_else(|e| panic!("{}", e));
+        for (b, x) in self.channels.iter_mut().zip(inputs[0].data()) {
+            b.update(t, &[*x]);
+        }
+    }
+
+    fn zero_crossings(&self, t: f64, inputs: &[Vector]) -> Vec<f64> {
+        self.check(&inputs[0]).unwrap_or_else(|e| panic!("{}", e));
+        self.channels
+            .iter()
+            .zip(inputs[0].data())
+            .flat_map(|(b, x)| b.zero_crossings(t, &[*x]))
+            .collect()
+    }
+
+    fn reset(&mut self) {
+        self.channels.iter_mut().for_each(|b| b.reset())
+    }
+}
+
+// Clamps every element to the same limits.
+impl Transfer<Vector, Vector> for Saturation {
+    fn transfer(&self, _t: f64, input: &Vector) -> Vector {
+        input.map(|x| self.saturation(*x))
+    }
+
+    fn zero_crossings(&self, t: f64, input: &Vector) -> Vec<f64> {
+        input
+            .data()
+            .iter()
+            .flat_map(|x| Transfer::<f64, f64>::zero_crossings(self, t, x))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VectorLimits {
+    Scalar { top: f64, bottom: f64 },
+    Elementwise { top: Vector, bottom: Vector },
+    // Scales the whole vector down so its norm does not exceed the limit,
+    // keeping its direction.
+    Norm(f64),
+}
+
+#[derive(Debug, Clone)]
+pub struct VectorSaturation {
+    limits: VectorLimits,
+}
+
+impl VectorSaturation {
+    pub fn new(top: f64, bottom: f64) -> Result<Self, SimError> {
+        if top.is_nan() || bottom.is_nan() || bottom > top {
+            return Err(SimError::InvalidParameter(format!(
+                "invalid saturation limits [{bottom}, {top}]"
+            )));
+        }
+        Ok(Self {
+            limits: VectorLimits::Scalar { top, bottom },
+        })
+    }
+
+    pub fn elementwise(top: Vector, bottom: Vector) -> Result<Self, SimError> {
+        top.check_dim(&bottom)?;
+        if let Some(i) =
+            (0..top.dim()).find(|i| bottom[*i].is_nan() || top[*i].is_nan() || bottom[*i] > top[*i])
+        {
+            return Err(SimError::InvalidParameter(format!(
+                "invalid saturation limits [{}, {}] for element {i}",
+                bottom[i], top[i]
+            )));
+        }
+        Ok(Self {
+            limits: VectorLimits::Elementwise { top, bottom },
+        })
+    }
+
+    pub fn norm(limit: f64) -> Result<Self, SimError> {
+        if limit.is_nan() || limit < 0.0 {
+            return Err(SimError::InvalidParameter(format!(
+                "norm limit must be non-negative, got {limit}"
+            )));
+        }
+        Ok(Self {
+            limits: VectorLimits::Norm(limit),
+        })
+    }
+
+    pub fn limits(&self) -> &VectorLimits {
+        &self.limits
+    }
+
+    pub fn try_saturate(&self, x: &Vector) -> Result<Vector, SimError> {
+        match &self.limits {
+            VectorLimits::Scalar { top, bottom } => Ok(x.map(|v| v.clamp(*bottom, *top))),
+            VectorLimits::Elementwise { top, bottom } => {
+                top.check_dim(x)?;
+                Ok(Vector::from(
+                    (0..x.dim())
+                        .map(|i| x[i].clamp(bottom[i], top[i]))
+                        .collect::<Vec<_>>(),
+                ))
+            }
+            VectorLimits::Norm(limit) => {
+                let norm = x.norm();
+                if norm > *limit {
+                    Ok(x.clone() * (limit / norm))
+                } else {
+                    Ok(x.clone())
+                }
+            }
+        }
+    }
+}
+
+impl Transfer<Vector, Vector> for VectorSaturation {
+    fn transfer(&self, _t: f64, input: &Vector) -> Vector {
+        self.try_saturate(input).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn zero_crossings(&self, _t: f64, input: &Vector) -> Vec<f64> {
+        let x = input;
+        match &self.limits {
+            VectorLimits::Scalar { top, bottom } => x
+                .data()
+                .iter()
+                .flat_map(|v| [v - top, v - bottom])
+                .collect(),
+            VectorLimits::Elementwise { top, bottom } => (0..x.dim().min(top.dim()))
+                .flat_map(|i| [x[i] - top[i], x[i] - bottom[i]])
+                .collect(),
+            VectorLimits::Norm(limit) => vec![x.norm() - limit],
+        }
+    }
+}
+
+impl Block<Vector> for VectorSaturation {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, inputs: &[Vector]) -> Vec<Vector> {
+        vec![self.transfer(t, &inputs[0])]
+    }
+
+    fn zero_crossings(&self, t: f64, inputs: &[Vector]) -> Vec<f64> {
+        Transfer::zero_crossings(self, t, &inputs[0])
+    }
+}
+
+#[cfg(test)]
+mod vector_tests {
+    use super::*;
+    use crate::discontinuous::{DeadZone, Relay};
+
+    #[test]
+    fn test_elementwise() {
+        let sat = Elementwise::new(vec![
+            Saturation::new(1.0, -1.0).unwrap(),
+            Saturation::new(2.0, 0.0).unwrap(),
+        ]);
+        let y = sat.transfer(0.0, &Vector::from(vec![3.0, -3.0]));
+        assert_eq!(y, Vector::from(vec![1.0, 0.0]));
+        let dz = Elementwise::repeat(DeadZone::new(-1.0, 1.0).unwrap(), 3);
+        assert_eq!(Block::zero_crossings(&dz, 0.0, &[Vector::zero(3)]).len(), 6);
+
+        // Each relay switches on its own.
+        let mut relays = Elementwise::repeat(Relay::new(1.0, -1.0, 1.0, 0.0).unwrap(), 2);
+        relays.update(0.0, &[Vector::from(vec![2.0, 0.0])]);
+        let y = relays.output(1.0, &[Vector::from(vec![0.0, 0.0])]);
+        assert_eq!(y[0], Vector::from(vec![1.0, 0.0]));
+        assert_eq!(
+            relays.check(&Vector::zero(3)),
+            Err(SimError::DimensionMismatch {
+                expected: 2,
+                found: 3
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "dimension mismatch")]
+    fn test_elementwise_dimension() {
+        let sat = Elementwise::repeat(Saturation::new(1.0, -1.0).unwrap(), 2);
+        sat.transfer(0.0, &Vector::zero(3));
+    }
+
+    #[test]
+    fn test_saturation() {
+        let x = Vector::from(vec![3.0, -0.5, -4.0]);
+        let scalar = Saturation::new(1.0, -2.0).unwrap();
+        assert_eq!(
+            Transfer::<Vector, Vector>::transfer(&scalar, 0.0, &x),
+            Vector::from(vec![1.0, -0.5, -2.0])
+        );
+        let per_element = VectorSaturation::elementwise(
+            Vector::from(vec![4.0, 0.0, 1.0]),
+            Vector::from(vec![0.0, -1.0, -1.0]),
+        )
+        .unwrap();
+        assert_eq!(
+            per_element.transfer(0.0, &x),
+            Vector::from(vec![3.0, -0.5, -1.0])
+        );
+        assert!(per_element.try_saturate(&Vector::zero(2)).is_err());
+        assert!(VectorSaturation::elementwise(Vector::zero(2), Vector::ones(2)).is_err());
+
+        // The norm limit keeps the direction of the command.
+        let thrust = VectorSaturation::norm(2.5).unwrap();
+        let y = thrust.transfer(0.0, &Vector::from(vec![3.0, 4.0]));
+        assert!((y[0] - 1.5).abs() < 1e-12 && (y[1] - 2.0).abs() < 1e-12);
+        let small = Vector::from(vec![0.3, 0.4]);
+        assert_eq!(thrust.transfer(0.0, &small), small);
+        let g = Block::zero_crossings(&thrust, 0.0, &[small]);
+        assert!((g[0] + 2.0).abs() < 1e-12);
+    }
+}
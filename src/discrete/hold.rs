@@ -0,0 +1,196 @@
+use crate::{
+    block::{Block, SampleTime},
+    model::IsValue,
+};
+
+// Outputs its input from the previous sample hit.
+#[derive(Debug, Clone)]
+pub struct UnitDelay<V: IsValue> {
+    init: V,
+    state: V,
+    sample_time: SampleTime,
+}
+
+impl<V: IsValue> UnitDelay<V> {
+    pub fn new(init: V, sample_time: SampleTime) -> Self {
+        Self {
+            state: init.clone(),
+            init,
+            sample_time,
+        }
+    }
+
+    pub fn init(&self) -> &V {
+        &self.init
+    }
+
+    pub fn state(&self) -> &V {
+        &self.state
+    }
+
+    pub fn reset(&mut self) {
+        self.state = self.init.clone();
+    }
+}
+
+impl<V: IsValue> Block<V> for UnitDelay<V> {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, _t: f64, _inputs: &[V]) -> Vec<V> {
+        vec![self.state.clone()]
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        false
+    }
+
+    fn update(&mut self, _t: f64, inputs: &[V]) {
+        self.state = inputs[0].clone();
+    }
+
+    fn sample_time(&self) -> SampleTime {
+        self.sample_time
+    }
+
+    fn reset(&mut self) {
+        UnitDelay::reset(self)
+    }
+}
+
+// Samples its input at each hit; the engine holds the output in between.
+#[derive(Debug, Clone)]
+pub struct ZeroOrderHold {
+    sample_time: SampleTime,
+}
+
+impl ZeroOrderHold {
+    pub fn new(sample_time: SampleTime) -> Self {
+        Self { sample_time }
+    }
+}
+
+impl<V: IsValue> Block<V> for ZeroOrderHold {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, _t: f64, inputs: &[V]) -> Vec<V> {
+        vec![inputs[0].clone()]
+    }
+
+    fn sample_time(&self) -> SampleTime {
+        self.sample_time
+    }
+}
+
+// Extrapolates linearly from the last two samples, so the output is
+// continuous in time although the input is only read at the hits.
+#[derive(Debug, Clone)]
+pub struct FirstOrderHold {
+    init: f64,
+    last: Option<(f64, f64)>,
+    current: Option<(f64, f64)>,
+    sample_time: SampleTime,
+}
+
+impl FirstOrderHold {
+    pub fn new(init: f64, sample_time: SampleTime) -> Self {
+        Self {
+            init,
+            last: None,
+            current: None,
+            sample_time,
+        }
+    }
+
+    pub fn init(&self) -> f64 {
+        self.init
+    }
+
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.current = None;
+    }
+}
+
+impl Block<f64> for FirstOrderHold {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        let y = match (self.last, self.current) {
+            (Some((t0, u0)), Some((t1, u1))) => u1 + (u1 - u0) * (t - t1) / (t1 - t0),
+            (None, Some((_, u1))) => u1,
+            _ => self.init,
+        };
+        vec![y]
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        false
+    }
+
+    fn update(&mut self, t: f64, inputs: &[f64]) {
+        self.last = self.current;
+        self.current = Some((t, inputs[0]));
+    }
+
+    fn sample_time(&self) -> SampleTime {
+        self.sample_time
+    }
+
+    fn reset(&mut self) {
+        FirstOrderHold::reset(self)
+    }
+}
+
+#[cfg(test)]
+mod hold_tests {
+    use super::*;
+    use crate::{diagram::Diagram, simulation::Simulation, utils::fixtures::Ramp};
+
+    #[test]
+    fn test_holds() {
+        let mut d = Diagram::new();
+        let ramp = d.add_source("ramp", Ramp);
+        let zoh = d.add("zoh", ZeroOrderHold::new(SampleTime::discrete(0.25)));
+        let delay = d.add("delay", UnitDelay::new(-1.0, SampleTime::discrete(0.25)));
+        let foh = d.add("foh", FirstOrderHold::new(0.0, SampleTime::discrete(0.25)));
+        d.connect(ramp, 0, zoh, 0).unwrap();
+        d.connect(ramp, 0, delay, 0).unwrap();
+        d.connect(ramp, 0, foh, 0).unwrap();
+
+        // The grid does not contain the hits, so the engine has to add them.
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.4);
+        let zoh = sim.probe(zoh, 0).unwrap();
+        let delay = sim.probe(delay, 0).unwrap();
+        let foh = sim.probe(foh, 0).unwrap();
+        let r = sim.run().unwrap();
+        assert_eq!(r.times(), &[0.0, 0.25, 0.4, 0.5, 0.75, 0.8, 1.0]);
+        assert_eq!(
+            r.signal(zoh).unwrap(),
+            &[0.0, 0.25, 0.25, 0.5, 0.75, 0.75, 1.0]
+        );
+        assert_eq!(
+            r.signal(delay).unwrap(),
+            &[-1.0, 0.0, 0.25, 0.25, 0.5, 0.75, 0.75]
+        );
+        // Linear extrapolation of a ramp reproduces it once two samples exist.
+        assert!((r.signal(foh).unwrap()[2] - 0.4).abs() < 1e-12);
+    }
+}
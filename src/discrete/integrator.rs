@@ -0,0 +1,173 @@
+use crate::{
+    block::{Block, SampleTime},
+    utils::SimError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscreteMethod {
+    ForwardEuler,
+    BackwardEuler,
+    Trapezoidal,
+}
+
+// Accumulates `T * u` at each hit, with T the sample period, or the time
+// since the previous hit when the block is triggered. Forward Euler has no
+// direct feedthrough; the other methods include the current input in the
+// output. Both the output and the state are kept within the limits.
+#[derive(Debug, Clone)]
+pub struct DiscreteIntegrator {
+    init: f64,
+    state: f64,
+    last: Option<(f64, f64)>,
+    method: DiscreteMethod,
+    lower: f64,
+    upper: f64,
+    sample_time: SampleTime,
+    resolved: SampleTime,
+}
+
+impl DiscreteIntegrator {
+    pub fn new(init: f64, method: DiscreteMethod, sample_time: SampleTime) -> Self {
+        Self {
+            init,
+            state: init,
+            last: None,
+            method,
+            lower: f64::NEG_INFINITY,
+            upper: f64::INFINITY,
+            sample_time,
+            resolved: sample_time,
+        }
+    }
+
+    pub fn with_limits(
+        init: f64,
+        method: DiscreteMethod,
+        lower: f64,
+        upper: f64,
+        sample_time: SampleTime,
+    ) -> Result<Self, SimError> {
+        if lower.is_nan() || upper.is_nan() || lower > upper {
+            return Err(SimError::InvalidParameter(format!(
+                "invalid integrator limits [{lower}, {upper}]"
+            )));
+        }
+        let mut integrator = Self::new(init.clamp(lower, upper), method, sample_time);
+        integrator.lower = lower;
+        integrator.upper = upper;
+        Ok(integrator)
+    }
+
+    pub fn init(&self) -> f64 {
+        self.init
+    }
+
+    pub fn state(&self) -> f64 {
+        self.state
+    }
+
+    pub fn method(&self) -> DiscreteMethod {
+        self.method
+    }
+
+    pub fn lower(&self) -> f64 {
+        self.lower
+    }
+
+    pub fn upper(&self) -> f64 {
+        self.upper
+    }
+
+    pub fn reset(&mut self) {
+        self.state = self.init;
+        self.last = None;
+    }
+
+    fn step(&self, t: f64) -> f64 {
+        match (self.resolved, self.last) {
+            (SampleTime::Discrete { period, .. }, _) => period,
+            (_, Some((last, _))) => t - last,
+            (_, None) => 0.0,
+        }
+    }
+
+    // Integrator value at the current hit for the input `u`.
+    fn value(&self, t: f64, u: f64) -> f64 {
+        let last = self.last.map(|(_, u)| u);
+        let increment = match self.method {
+            DiscreteMethod::ForwardEuler => last.unwrap_or(0.0),
+            DiscreteMethod::BackwardEuler => u,
+            DiscreteMethod::Trapezoidal => last.map_or(0.0, |last| 0.5 * (u + last)),
+        };
+        (self.state + self.step(t) * increment).clamp(self.lower, self.upper)
+    }
+}
+
+impl Block<f64> for DiscreteIntegrator {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        vec![self.value(t, inputs.first().copied().unwrap_or_default())]
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        self.method != DiscreteMethod::ForwardEuler
+    }
+
+    fn update(&mut self, t: f64, inputs: &[f64]) {
+        self.state = self.value(t, inputs[0]);
+        self.last = Some((t, inputs[0]));
+    }
+
+    fn sample_time(&self) -> SampleTime {
+        self.sample_time
+    }
+
+    fn inherit_sample_time(&mut self, sample_time: SampleTime) {
+        self.resolved = sample_time;
+    }
+
+    fn reset(&mut self) {
+        DiscreteIntegrator::reset(self)
+    }
+}
+
+#[cfg(test)]
+mod integrator_tests {
+    use super::*;
+
+    fn run(mut i: DiscreteIntegrator, u: &[f64]) -> Vec<f64> {
+        u.iter()
+            .enumerate()
+            .map(|(k, u)| {
+                let t = k as f64;
+                let y = i.output(t, &[*u])[0];
+                i.update(t, &[*u]);
+                y
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_methods() {
+        let ts = SampleTime::discrete(1.0);
+        let u = [1.0, 2.0, 3.0, 4.0];
+        let forward = DiscreteIntegrator::new(0.0, DiscreteMethod::ForwardEuler, ts);
+        assert_eq!(run(forward, &u), vec![0.0, 1.0, 3.0, 6.0]);
+        let backward = DiscreteIntegrator::new(0.0, DiscreteMethod::BackwardEuler, ts);
+        assert_eq!(run(backward, &u), vec![1.0, 3.0, 6.0, 10.0]);
+        let trapezoid = DiscreteIntegrator::new(0.0, DiscreteMethod::Trapezoidal, ts);
+        assert_eq!(run(trapezoid, &u), vec![0.0, 1.5, 4.0, 7.5]);
+        let method = DiscreteMethod::BackwardEuler;
+        let limited = DiscreteIntegrator::with_limits(0.0, method, -1.0, 4.0, ts).unwrap();
+        assert_eq!(run(limited, &[3.0, 3.0, -2.0]), vec![3.0, 4.0, 2.0]);
+        assert!(DiscreteIntegrator::with_limits(0.0, method, 4.0, -1.0, ts).is_err());
+        assert!(DiscreteIntegrator::with_limits(0.0, method, f64::NAN, 1.0, ts).is_err());
+    }
+}
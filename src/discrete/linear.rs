@@ -0,0 +1,238 @@
+use crate::{
+    block::{Block, SampleTime},
+    model::{
+        linear::{affine, check_realization, controllable_canonical, shape},
+        Matrix,
+    },
+    utils::SimError,
+};
+
+// x[k+1] = A x[k] + B u[k], y[k] = C x[k] + D u[k], with one scalar port per
+// input and output.
+#[derive(Debug, Clone)]
+pub struct DiscreteStateSpace {
+    a: Matrix,
+    b: Matrix,
+    c: Matrix,
+    d: Matrix,
+    init: Vec<f64>,
+    state: Vec<f64>,
+    sample_time: SampleTime,
+}
+
+impl DiscreteStateSpace {
+    pub fn new(
+        a: Matrix,
+        b: Matrix,
+        c: Matrix,
+        d: Matrix,
+        sample_time: SampleTime,
+    ) -> Result<Self, SimError> {
+        let (n, _, _) = check_realization(&a, &b, &c, &d)?;
+        Ok(Self {
+            a,
+            b,
+            c,
+            d,
+            init: vec![0.0; n],
+            state: vec![0.0; n],
+            sample_time,
+        })
+    }
+
+    pub fn a(&self) -> &Matrix {
+        &self.a
+    }
+
+    pub fn b(&self) -> &Matrix {
+        &self.b
+    }
+
+    pub fn c(&self) -> &Matrix {
+        &self.c
+    }
+
+    pub fn d(&self) -> &Matrix {
+        &self.d
+    }
+
+    pub fn state(&self) -> &[f64] {
+        &self.state
+    }
+
+    pub fn set_init(&mut self, init: &[f64]) -> Result<(), SimError> {
+        if init.len() != self.state.len() {
+            return Err(SimError::DimensionMismatch {
+                expected: self.state.len(),
+                found: init.len(),
+            });
+        }
+        self.init = init.to_vec();
+        self.state = init.to_vec();
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        self.state = self.init.clone();
+    }
+}
+
+impl Block<f64> for DiscreteStateSpace {
+    fn num_inputs(&self) -> usize {
+        shape(&self.d).1
+    }
+
+    fn num_outputs(&self) -> usize {
+        shape(&self.d).0
+    }
+
+    fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+        let p = self.num_outputs();
+        if self.direct_feedthrough() {
+            affine(&self.c, &self.state, &self.d, inputs, p)
+        } else {
+            affine(&self.c, &self.state, &self.d, &[], p)
+        }
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        self.d.data().iter().any(|v| *v != 0.0)
+    }
+
+    fn update(&mut self, _t: f64, inputs: &[f64]) {
+        self.state = affine(&self.a, &self.state, &self.b, inputs, self.state.len());
+    }
+
+    fn sample_time(&self) -> SampleTime {
+        self.sample_time
+    }
+
+    fn reset(&mut self) {
+        DiscreteStateSpace::reset(self)
+    }
+}
+
+// Single-input single-output transfer function in z with coefficients in
+// descending powers, realised in controllable canonical form.
+#[derive(Debug, Clone)]
+pub struct DiscreteTransferFunction {
+    num: Vec<f64>,
+    den: Vec<f64>,
+    realization: DiscreteStateSpace,
+}
+
+impl DiscreteTransferFunction {
+    pub fn new(num: Vec<f64>, den: Vec<f64>, sample_time: SampleTime) -> Result<Self, SimError> {
+        let (a, b, c, d) = controllable_canonical(&num, &den)?;
+        let realization = DiscreteStateSpace::new(a, b, c, d, sample_time)?;
+        Ok(Self {
+            num,
+            den,
+            realization,
+        })
+    }
+
+    pub fn num(&self) -> &[f64] {
+        &self.num
+    }
+
+    pub fn den(&self) -> &[f64] {
+        &self.den
+    }
+
+    pub fn realization(&self) -> &DiscreteStateSpace {
+        &self.realization
+    }
+
+    pub fn reset(&mut self) {
+        self.realization.reset();
+    }
+}
+
+impl Block<f64> for DiscreteTransferFunction {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        self.realization.output(t, inputs)
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        self.realization.direct_feedthrough()
+    }
+
+    fn update(&mut self, t: f64, inputs: &[f64]) {
+        self.realization.update(t, inputs);
+    }
+
+    fn sample_time(&self) -> SampleTime {
+        self.realization.sample_time()
+    }
+
+    fn reset(&mut self) {
+        DiscreteTransferFunction::reset(self)
+    }
+}
+
+#[cfg(test)]
+mod linear_tests {
+    use super::*;
+
+    fn run(b: &mut impl Block<f64>, u: &[f64]) -> Vec<f64> {
+        u.iter()
+            .enumerate()
+            .map(|(k, u)| {
+                let y = b.output(k as f64, &[*u])[0];
+                b.update(k as f64, &[*u]);
+                y
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_transfer_function() {
+        let ts = SampleTime::discrete(1.0);
+        // y[k] = 0.5 y[k-1] + u[k-1]
+        let mut lag = DiscreteTransferFunction::new(vec![1.0], vec![1.0, -0.5], ts).unwrap();
+        assert!(!lag.direct_feedthrough());
+        assert_eq!(run(&mut lag, &[1.0; 4]), vec![0.0, 1.0, 1.5, 1.75]);
+        // (z + 1) / (2z) = 0.5 + 0.5 z^-1
+        let mut avg = DiscreteTransferFunction::new(vec![1.0, 1.0], vec![2.0, 0.0], ts).unwrap();
+        assert!(avg.direct_feedthrough());
+        assert_eq!(run(&mut avg, &[2.0, 4.0, 0.0]), vec![1.0, 3.0, 2.0]);
+        let mut gain = DiscreteTransferFunction::new(vec![3.0], vec![1.0], ts).unwrap();
+        assert_eq!(run(&mut gain, &[2.0]), vec![6.0]);
+        assert!(DiscreteTransferFunction::new(vec![1.0, 0.0], vec![1.0], ts).is_err());
+    }
+
+    #[test]
+    fn test_state_space() {
+        let ts = SampleTime::discrete(0.1);
+        let ss = DiscreteStateSpace::new(
+            Matrix::from(vec![vec![1.0, 0.1], vec![0.0, 1.0]]),
+            Matrix::from(vec![vec![0.0], vec![0.1]]),
+            Matrix::from(vec![vec![1.0, 0.0], vec![0.0, 1.0]]),
+            Matrix::from(vec![vec![0.0], vec![0.0]]),
+            ts,
+        );
+        let mut ss = ss.unwrap();
+        assert_eq!(ss.num_outputs(), 2);
+        ss.update(0.0, &[1.0]);
+        ss.update(0.1, &[1.0]);
+        let y = ss.output(0.2, &[]);
+        assert!((y[0] - 0.01).abs() < 1e-12 && (y[1] - 0.2).abs() < 1e-12);
+        assert!(DiscreteStateSpace::new(
+            Matrix::from(vec![vec![1.0]]),
+            Matrix::from(vec![vec![1.0, 2.0]]),
+            Matrix::from(vec![vec![1.0]]),
+            Matrix::from(vec![vec![0.0]]),
+            ts,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,7 @@
+pub(crate) mod hold;
+pub(crate) mod integrator;
+pub(crate) mod linear;
+
+pub use hold::{FirstOrderHold, UnitDelay, ZeroOrderHold};
+pub use integrator::{DiscreteIntegrator, DiscreteMethod};
+pub use linear::{DiscreteStateSpace, DiscreteTransferFunction};
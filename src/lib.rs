@@ -1,7 +1,10 @@
 pub(crate) mod block;
 pub use block::*;
 
+pub mod diagram;
 pub mod model;
+pub mod simulation;
+pub mod solver;
 pub mod utils;
 
 #[cfg(feature = "source")]
@@ -15,3 +18,9 @@ pub mod continuous;
 
 #[cfg(feature = "discontinuous")]
 pub mod discontinuous;
+
+#[cfg(feature = "lookup")]
+pub mod lookup;
+
+#[cfg(feature = "discrete")]
+pub mod discrete;
@@ -0,0 +1,618 @@
+use crate::{
+    block::{Block, Transfer},
+    model::{Matrix, Vector},
+    utils::SimError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Linear,
+    Nearest,
+    CubicSpline,
+    Akima,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Extrapolation {
+    Clip,
+    Linear,
+    Error,
+}
+
+// Interpolation along one axis as a weighted sum of the table values and knot
+// slopes at breakpoints `index` and `index + 1`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Weights {
+    index: usize,
+    value: [f64; 2],
+    slope: [f64; 2],
+}
+
+impl Weights {
+    pub(crate) fn new(
+        xs: &[f64],
+        x: f64,
+        interpolation: Interpolation,
+        extrapolation: Extrapolation,
+    ) -> Result<Self, SimError> {
+        let n = xs.len();
+        let (min, max) = (xs[0], xs[n - 1]);
+        let outside = !(min..=max).contains(&x);
+        let x = match extrapolation {
+            _ if x.is_nan() => {
+                return Ok(Self {
+                    index: 0,
+                    value: [f64::NAN; 2],
+                    slope: [0.0; 2],
+                })
+            }
+            Extrapolation::Error if outside => {
+                return Err(SimError::OutOfRange { value: x, min, max })
+            }
+            Extrapolation::Clip => x.clamp(min, max),
+            _ => x,
+        };
+        if n == 1 {
+            return Ok(Self {
+                index: 0,
+                value: [1.0, 0.0],
+                slope: [0.0; 2],
+            });
+        }
+        let index = (xs.partition_point(|b| *b <= x).max(1) - 1).min(n - 2);
+        let h = xs[index + 1] - xs[index];
+        let s = (x - xs[index]) / h;
+        let (value, slope) = match interpolation {
+            Interpolation::Nearest if !outside || extrapolation == Extrapolation::Clip => {
+                (if s < 0.5 { [1.0, 0.0] } else { [0.0, 1.0] }, [0.0; 2])
+            }
+            Interpolation::Linear | Interpolation::Nearest => ([1.0 - s, s], [0.0; 2]),
+            // Cubic methods extrapolate along the end slopes.
+            _ if x < min => ([1.0, 0.0], [x - min, 0.0]),
+            _ if x > max => ([0.0, 1.0], [0.0, x - max]),
+            _ => {
+                // Cubic Hermite basis on the segment.
+                let (s2, s3) = (s * s, s * s * s);
+                (
+                    [2.0 * s3 - 3.0 * s2 + 1.0, -2.0 * s3 + 3.0 * s2],
+                    [(s3 - 2.0 * s2 + s) * h, (s3 - s2) * h],
+                )
+            }
+        };
+        Ok(Self {
+            index,
+            value,
+            slope,
+        })
+    }
+
+    // Table index and weights of the value and slope at corner `c`, 0 or 1.
+    // With a single breakpoint both corners refer to it.
+    fn corner(&self, c: usize, n: usize) -> (usize, f64, f64) {
+        ((self.index + c).min(n - 1), self.value[c], self.slope[c])
+    }
+
+    // `slopes` may be empty for linear and nearest interpolation, which give
+    // them no weight.
+    pub(crate) fn apply(&self, n: usize, ys: impl Fn(usize) -> f64, slopes: &[f64]) -> f64 {
+        let mut sum = 0.0;
+        for c in 0..2 {
+            let (j, value, slope) = self.corner(c, n);
+            if value != 0.0 {
+                sum += value * ys(j);
+            }
+            if slope != 0.0 {
+                sum += slope * slopes[j];
+            }
+        }
+        sum
+    }
+}
+
+// Knot slopes for the cubic methods, empty for the others.
+pub(crate) fn slopes(xs: &[f64], ys: &[f64], interpolation: Interpolation) -> Vec<f64> {
+    match interpolation {
+        _ if xs.len() < 2 => vec![0.0; xs.len()],
+        Interpolation::CubicSpline => spline_slopes(xs, ys),
+        Interpolation::Akima => akima_slopes(xs, ys),
+        Interpolation::Linear | Interpolation::Nearest => Vec::new(),
+    }
+}
+
+// Knot slopes of the natural cubic spline, from the tridiagonal system on the
+// first derivatives solved with the Thomas algorithm.
+fn spline_slopes(xs: &[f64], ys: &[f64]) -> Vec<f64> {
+    let n = xs.len();
+    let h = xs.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
+    let delta = (0..n - 1)
+        .map(|i| (ys[i + 1] - ys[i]) / h[i])
+        .collect::<Vec<_>>();
+    let (mut lower, mut diag, mut upper, mut rhs) =
+        (vec![0.0; n], vec![0.0; n], vec![0.0; n], vec![0.0; n]);
+    (diag[0], upper[0], rhs[0]) = (2.0, 1.0, 3.0 * delta[0]);
+    for i in 1..n - 1 {
+        lower[i] = h[i];
+        diag[i] = 2.0 * (h[i - 1] + h[i]);
+        upper[i] = h[i - 1];
+        rhs[i] = 3.0 * (h[i] * delta[i - 1] + h[i - 1] * delta[i]);
+    }
+    (lower[n - 1], diag[n - 1], rhs[n - 1]) = (1.0, 2.0, 3.0 * delta[n - 2]);
+    for i in 1..n {
+        let m = lower[i] / diag[i - 1];
+        diag[i] -= m * upper[i - 1];
+        rhs[i] -= m * rhs[i - 1];
+    }
+    let mut d = vec![0.0; n];
+    d[n - 1] = rhs[n - 1] / diag[n - 1];
+    for i in (0..n - 1).rev() {
+        d[i] = (rhs[i] - upper[i] * d[i + 1]) / diag[i];
+    }
+    d
+}
+
+// Akima's slopes, which follow the local trend and avoid the overshoot of the
+// spline near abrupt changes. The segment slopes are extended by two on each
+// side by linear extrapolation.
+fn akima_slopes(xs: &[f64], ys: &[f64]) -> Vec<f64> {
+    let n = xs.len();
+    let mut m = vec![0.0; n + 3];
+    for i in 0..n - 1 {
+        m[i + 2] = (ys[i + 1] - ys[i]) / (xs[i + 1] - xs[i]);
+    }
+    if n == 2 {
+        return vec![m[2]; 2];
+    }
+    m[1] = 2.0 * m[2] - m[3];
+    m[0] = 2.0 * m[1] - m[2];
+    m[n + 1] = 2.0 * m[n] - m[n - 1];
+    m[n + 2] = 2.0 * m[n + 1] - m[n];
+    (0..n)
+        .map(|i| {
+            let w1 = (m[i + 3] - m[i + 2]).abs();
+            let w2 = (m[i + 1] - m[i]).abs();
+            if w1 + w2 == 0.0 {
+                0.5 * (m[i + 1] + m[i + 2])
+            } else {
+                (w1 * m[i + 1] + w2 * m[i + 2]) / (w1 + w2)
+            }
+        })
+        .collect()
+}
+
+fn check_breakpoints(xs: &[f64]) -> Result<(), SimError> {
+    if xs.is_empty() {
+        return Err(SimError::EmptyVector);
+    }
+    if xs.iter().any(|x| x.is_nan()) || xs.windows(2).any(|w| w[1] <= w[0]) {
+        return Err(SimError::InvalidParameter(
+            "breakpoints must be strictly increasing".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// In the block and transfer implementations a lookup that fails under
+// `Extrapolation::Error` outputs NaN, which the simulation reports as
+// `SimError::NanEncountered` naming the block.
+#[derive(Debug, Clone)]
+pub struct Lookup1D {
+    table: LookupND,
+    values: Vector,
+}
+
+impl Lookup1D {
+    // Breakpoints may be given in any order; they are sorted together with
+    // their values.
+    pub fn new(
+        breakpoints: Vector,
+        values: Vector,
+        interpolation: Interpolation,
+        extrapolation: Extrapolation,
+    ) -> Result<Self, SimError> {
+        breakpoints.check_dim(&values)?;
+        if breakpoints.has_nan() {
+            return Err(SimError::InvalidParameter(
+                "breakpoints must not be NaN".to_string(),
+            ));
+        }
+        let order = breakpoints.arg_sort();
+        let breakpoints = Vector::from(order.iter().map(|i| breakpoints[*i]).collect::<Vec<_>>());
+        let values = Vector::from(order.iter().map(|i| values[*i]).collect::<Vec<_>>());
+        let table = LookupND::new(
+            vec![breakpoints],
+            values.data().to_vec(),
+            interpolation,
+            extrapolation,
+        )?;
+        Ok(Self { table, values })
+    }
+
+    pub fn breakpoints(&self) -> &Vector {
+        &self.table.breakpoints()[0]
+    }
+
+    pub fn values(&self) -> &Vector {
+        &self.values
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.table.interpolation()
+    }
+
+    pub fn extrapolation(&self) -> Extrapolation {
+        self.table.extrapolation()
+    }
+
+    pub fn lookup(&self, x: f64) -> Result<f64, SimError> {
+        self.table.lookup(&[x])
+    }
+}
+
+impl Transfer<f64, f64> for Lookup1D {
+    fn transfer(&self, _t: f64, input: &f64) -> f64 {
+        self.lookup(*input).unwrap_or(f64::NAN)
+    }
+}
+
+impl Transfer<Vector, Vector> for Lookup1D {
+    fn transfer(&self, _t: f64, input: &Vector) -> Vector {
+        input.map(|x| self.lookup(*x).unwrap_or(f64::NAN))
+    }
+}
+
+// Breakpoints for each axis and the table flattened in row-major order, the
+// last axis varying fastest. The cubic methods interpolate with the tensor
+// product Hermite basis: `new` computes the knot slopes along each subset of
+// the axes, so a lookup reads only the corners of the enclosing cell.
+#[derive(Debug, Clone)]
+pub struct LookupND {
+    breakpoints: Vec<Vector>,
+    strides: Vec<usize>,
+    // Indexed by a bit mask of the axes differentiated along, the values
+    // first.
+    derivatives: Vec<Vec<f64>>,
+    interpolation: Interpolation,
+    extrapolation: Extrapolation,
+}
+
+impl LookupND {
+    pub fn new(
+        breakpoints: Vec<Vector>,
+        values: Vec<f64>,
+        interpolation: Interpolation,
+        extrapolation: Extrapolation,
+    ) -> Result<Self, SimError> {
+        if breakpoints.is_empty() {
+            return Err(SimError::InvalidParameter(
+                "lookup table needs at least one axis".to_string(),
+            ));
+        }
+        for b in breakpoints.iter() {
+            check_breakpoints(b.data())?;
+        }
+        let len = breakpoints.iter().map(|b| b.dim()).product();
+        if values.len() != len {
+            return Err(SimError::DimensionMismatch {
+                expected: len,
+                found: values.len(),
+            });
+        }
+        let strides = (0..breakpoints.len())
+            .map(|k| breakpoints[k + 1..].iter().map(|b| b.dim()).product())
+            .collect::<Vec<usize>>();
+
+        let mut derivatives = vec![values];
+        if matches!(
+            interpolation,
+            Interpolation::CubicSpline | Interpolation::Akima
+        ) {
+            for mask in 1..1usize << breakpoints.len() {
+                let axis = mask.trailing_zeros() as usize;
+                let base = &derivatives[mask & !(1 << axis)];
+                let (xs, stride) = (breakpoints[axis].data(), strides[axis]);
+                let mut out = vec![0.0; len];
+                // Each line along `axis` starts where its index there is 0.
+                for start in (0..len).filter(|i| (i / stride) % xs.len() == 0) {
+                    let line = (0..xs.len())
+                        .map(|j| base[start + j * stride])
+                        .collect::<Vec<_>>();
+                    for (j, d) in slopes(xs, &line, interpolation).into_iter().enumerate() {
+                        out[start + j * stride] = d;
+                    }
+                }
+                derivatives.push(out);
+            }
+        }
+        Ok(Self {
+            breakpoints,
+            strides,
+            derivatives,
+            interpolation,
+            extrapolation,
+        })
+    }
+
+    pub fn breakpoints(&self) -> &[Vector] {
+        &self.breakpoints
+    }
+
+    pub fn values(&self) -> &[f64] {
+        &self.derivatives[0]
+    }
+
+    pub fn shape(&self) -> Vec<usize> {
+        self.breakpoints.iter().map(|b| b.dim()).collect()
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    pub fn extrapolation(&self) -> Extrapolation {
+        self.extrapolation
+    }
+
+    pub fn lookup(&self, point: &[f64]) -> Result<f64, SimError> {
+        if point.len() != self.breakpoints.len() {
+            return Err(SimError::DimensionMismatch {
+                expected: self.breakpoints.len(),
+                found: point.len(),
+            });
+        }
+        let weights = self
+            .breakpoints
+            .iter()
+            .zip(point.iter())
+            .map(|(b, x)| Weights::new(b.data(), *x, self.interpolation, self.extrapolation))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut sum = 0.0;
+        for corner in 0..1usize << weights.len() {
+            for (mask, table) in self.derivatives.iter().enumerate() {
+                let (mut weight, mut offset) = (1.0, 0);
+                for (axis, w) in weights.iter().enumerate() {
+                    let (j, value, slope) =
+                        w.corner((corner >> axis) & 1, self.breakpoints[axis].dim());
+                    weight *= if (mask >> axis) & 1 == 1 {
+                        slope
+                    } else {
+                        value
+                    };
+                    offset += j * self.strides[axis];
+                }
+                if weight != 0.0 {
+                    sum += weight * table[offset];
+                }
+            }
+        }
+        Ok(sum)
+    }
+}
+
+impl Transfer<Vector, f64> for LookupND {
+    fn transfer(&self, _t: f64, input: &Vector) -> f64 {
+        self.lookup(input.data()).unwrap_or(f64::NAN)
+    }
+}
+
+// One scalar input per axis.
+impl Block<f64> for LookupND {
+    fn num_inputs(&self) -> usize {
+        self.breakpoints.len()
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+        vec![self.lookup(inputs).unwrap_or(f64::NAN)]
+    }
+}
+
+// Rows of `values` follow the `x` breakpoints and columns the `y` ones.
+#[derive(Debug, Clone)]
+pub struct Lookup2D {
+    table: LookupND,
+    values: Matrix,
+}
+
+impl Lookup2D {
+    pub fn new(
+        x: Vector,
+        y: Vector,
+        values: Matrix,
+        interpolation: Interpolation,
+        extrapolation: Extrapolation,
+    ) -> Result<Self, SimError> {
+        let (rows, cols) = values.try_shape()?;
+        if rows != x.dim() {
+            return Err(SimError::DimensionMismatch {
+                expected: x.dim(),
+                found: rows,
+            });
+        }
+        if cols != y.dim() {
+            return Err(SimError::DimensionMismatch {
+                expected: y.dim(),
+                found: cols,
+            });
+        }
+        let flat = values.ravel().to_vec();
+        let table = LookupND::new(vec![x, y], flat, interpolation, extrapolation)?;
+        Ok(Self { table, values })
+    }
+
+    pub fn x(&self) -> &Vector {
+        &self.table.breakpoints()[0]
+    }
+
+    pub fn y(&self) -> &Vector {
+        &self.table.breakpoints()[1]
+    }
+
+    pub fn values(&self) -> &Matrix {
+        &self.values
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.table.interpolation()
+    }
+
+    pub fn extrapolation(&self) -> Extrapolation {
+        self.table.extrapolation()
+    }
+
+    pub fn lookup(&self, x: f64, y: f64) -> Result<f64, SimError> {
+        self.table.lookup(&[x, y])
+    }
+}
+
+impl Transfer<Vector, f64> for Lookup2D {
+    fn transfer(&self, t: f64, input: &Vector) -> f64 {
+        self.table.transfer(t, input)
+    }
+}
+
+impl Block<f64> for Lookup2D {
+    fn num_inputs(&self) -> usize {
+        2
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, inputs: &[f64]) -> Vec<f64> {
+        self.table.output(t, inputs)
+    }
+}
+
+#[cfg(test)]
+mod lookup_tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_1d() {
+        let x = Vector::from(vec![2.0, 0.0, 1.0]);
+        let y = Vector::from(vec![4.0, 0.0, 1.0]);
+        let linear = Lookup1D::new(
+            x.clone(),
+            y.clone(),
+            Interpolation::Linear,
+            Extrapolation::Linear,
+        )
+        .unwrap();
+        assert_eq!(linear.breakpoints(), &Vector::from(vec![0.0, 1.0, 2.0]));
+        assert_eq!(linear.lookup(1.5), Ok(2.5));
+        assert_eq!(linear.lookup(3.0), Ok(7.0));
+        assert_eq!(linear.lookup(-1.0), Ok(-1.0));
+
+        let nearest = Lookup1D::new(
+            x.clone(),
+            y.clone(),
+            Interpolation::Nearest,
+            Extrapolation::Clip,
+        )
+        .unwrap();
+        assert_eq!(nearest.lookup(1.4), Ok(1.0));
+        assert_eq!(nearest.lookup(1.6), Ok(4.0));
+        assert_eq!(nearest.lookup(9.0), Ok(4.0));
+
+        let error = Lookup1D::new(x, y, Interpolation::Linear, Extrapolation::Error).unwrap();
+        assert_eq!(
+            error.lookup(2.5),
+            Err(SimError::OutOfRange {
+                value: 2.5,
+                min: 0.0,
+                max: 2.0
+            })
+        );
+        assert!(error.transfer(0.0, &2.5).is_nan());
+        assert!(Lookup1D::new(
+            Vector::from(vec![0.0, 0.0]),
+            Vector::from(vec![1.0, 2.0]),
+            Interpolation::Linear,
+            Extrapolation::Clip
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_cubic() {
+        // Both cubic methods reproduce a straight line exactly, and the spline
+        // stays close to a smooth function between breakpoints.
+        let x = Vector::from((0..6).map(|i| i as f64).collect::<Vec<_>>());
+        let line = x.map(|v| 2.0 * v + 1.0);
+        for method in [Interpolation::CubicSpline, Interpolation::Akima] {
+            let l = Lookup1D::new(x.clone(), line.clone(), method, Extrapolation::Linear).unwrap();
+            assert!((l.lookup(2.3).unwrap() - 5.6).abs() < 1e-12);
+            assert!((l.lookup(7.0).unwrap() - 15.0).abs() < 1e-12);
+        }
+        let x = Vector::from(
+            (0..=20)
+                .map(|i| i as f64 * 0.1 * std::f64::consts::PI)
+                .collect::<Vec<_>>(),
+        );
+        let spline = Lookup1D::new(
+            x.clone(),
+            x.map(|v| v.sin()),
+            Interpolation::CubicSpline,
+            Extrapolation::Clip,
+        )
+        .unwrap();
+        assert!((spline.lookup(1.0).unwrap() - 1.0f64.sin()).abs() < 1e-3);
+
+        // Akima does not overshoot a step.
+        let x = Vector::from((0..6).map(|i| i as f64).collect::<Vec<_>>());
+        let step = Vector::from(vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+        let akima = Lookup1D::new(x, step, Interpolation::Akima, Extrapolation::Clip).unwrap();
+        assert_eq!(akima.lookup(0.5), Ok(0.0));
+        assert_eq!(akima.lookup(4.5), Ok(1.0));
+    }
+
+    #[test]
+    fn test_lookup_2d() {
+        let x = Vector::from(vec![0.0, 1.0]);
+        let y = Vector::from(vec![0.0, 1.0, 2.0]);
+        let values = Matrix::from(vec![vec![0.0, 1.0, 2.0], vec![10.0, 11.0, 12.0]]);
+        let mut l =
+            Lookup2D::new(x, y, values, Interpolation::Linear, Extrapolation::Clip).unwrap();
+        assert_eq!(l.lookup(0.5, 1.5), Ok(6.5));
+        assert_eq!(l.lookup(2.0, -1.0), Ok(10.0));
+        assert_eq!(Block::output(&mut l, 0.0, &[0.25, 2.0]), vec![4.5]);
+
+        let nd = LookupND::new(
+            vec![Vector::from(vec![0.0, 1.0]); 3],
+            (0..8).map(|i| i as f64).collect(),
+            Interpolation::CubicSpline,
+            Extrapolation::Error,
+        )
+        .unwrap();
+        // The table is the trilinear function 4x + 2y + z.
+        assert!((nd.lookup(&[0.5, 0.5, 0.5]).unwrap() - 3.5).abs() < 1e-12);
+        assert!(nd.lookup(&[0.5, 0.5]).is_err());
+        assert!(nd.lookup(&[0.5, 1.5, 0.5]).is_err());
+
+        // A table constant along y interpolates like its profile along x.
+        let x = Vector::from(vec![0.0, 1.0, 3.0, 4.0, 6.0]);
+        let profile = x.map(|v| (0.7 * v).sin());
+        let y = Vector::from(vec![-1.0, 0.0, 2.0]);
+        let values = Matrix::from_fn((5, 3), |i, _| profile[i]);
+        for method in [Interpolation::CubicSpline, Interpolation::Akima] {
+            let line =
+                Lookup1D::new(x.clone(), profile.clone(), method, Extrapolation::Linear).unwrap();
+            let table = Lookup2D::new(
+                x.clone(),
+                y.clone(),
+                values.clone(),
+                method,
+                Extrapolation::Linear,
+            )
+            .unwrap();
+            for (u, v) in [(0.3, -0.5), (2.5, 1.7), (5.9, 0.0), (7.0, 3.0), (-1.0, 0.5)] {
+                let expected = line.lookup(u).unwrap();
+                assert!((table.lookup(u, v).unwrap() - expected).abs() < 1e-12);
+            }
+        }
+    }
+}
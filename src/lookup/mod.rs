@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+pub(crate) mod lookup;
+pub(crate) mod table;
+pub use lookup::{Extrapolation, Interpolation, Lookup1D, Lookup2D, LookupND};
+pub use table::FromTable;
@@ -0,0 +1,160 @@
+use super::lookup::{slopes, Extrapolation, Interpolation, Weights};
+use crate::{
+    block::Source,
+    model::{Matrix, Vector},
+    utils::SimError,
+};
+
+// Plays back samples given one row per time, one column per channel.
+// Outside the recorded times `Extrapolation::Error` outputs NaN.
+#[derive(Debug, Clone)]
+pub struct FromTable {
+    times: Vector,
+    values: Matrix,
+    // Knot slopes of each channel for the cubic methods.
+    slopes: Vec<Vec<f64>>,
+    interpolation: Interpolation,
+    extrapolation: Extrapolation,
+}
+
+impl FromTable {
+    pub fn new(
+        times: Vector,
+        values: Matrix,
+        interpolation: Interpolation,
+        extrapolation: Extrapolation,
+    ) -> Result<Self, SimError> {
+        let (rows, _) = values.try_shape()?;
+        if rows != times.dim() {
+            return Err(SimError::DimensionMismatch {
+                expected: times.dim(),
+                found: rows,
+            });
+        }
+        if times.has_nan() || times.data().windows(2).any(|w| w[1] <= w[0]) {
+            return Err(SimError::InvalidParameter(
+                "table times must be strictly increasing".to_string(),
+            ));
+        }
+        let slopes = values
+            .iter_columns()
+            .map(|c| {
+                slopes(
+                    times.data(),
+                    &c.iter().copied().collect::<Vec<_>>(),
+                    interpolation,
+                )
+            })
+            .collect();
+        Ok(Self {
+            times,
+            values,
+            slopes,
+            interpolation,
+            extrapolation,
+        })
+    }
+
+    pub fn from_pairs(
+        pairs: &[(f64, f64)],
+        interpolation: Interpolation,
+        extrapolation: Extrapolation,
+    ) -> Result<Self, SimError> {
+        let times = Vector::from(pairs.iter().map(|p| p.0).collect::<Vec<_>>());
+        let values = Matrix::from(pairs.iter().map(|p| vec![p.1]).collect::<Vec<_>>());
+        Self::new(times, values, interpolation, extrapolation)
+    }
+
+    pub fn times(&self) -> &Vector {
+        &self.times
+    }
+
+    pub fn values(&self) -> &Matrix {
+        &self.values
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    pub fn extrapolation(&self) -> Extrapolation {
+        self.extrapolation
+    }
+
+    pub fn sample(&self, t: f64, channel: usize) -> Result<f64, SimError> {
+        let (rows, channels) = self.values.shape();
+        if channel >= channels {
+            return Err(SimError::OutOfRange {
+                value: channel as f64,
+                min: 0.0,
+                max: channels as f64 - 1.0,
+            });
+        }
+        let weights = Weights::new(self.times.data(), t, self.interpolation, self.extrapolation)?;
+        Ok(weights.apply(rows, |j| self.values[(j, channel)], &self.slopes[channel]))
+    }
+}
+
+impl Source<f64> for FromTable {
+    fn output(&self, t: f64) -> f64 {
+        self.sample(t, 0).unwrap_or(f64::NAN)
+    }
+}
+
+impl Source<Vector> for FromTable {
+    fn output(&self, t: f64) -> Vector {
+        let (_, channels) = self.values.shape();
+        Vector::from(
+            (0..channels)
+                .map(|c| self.sample(t, c).unwrap_or(f64::NAN))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod table_tests {
+    use super::*;
+
+    #[test]
+    fn test_from_table() {
+        let thrust = FromTable::from_pairs(
+            &[(0.0, 0.0), (0.5, 100.0), (2.0, 40.0)],
+            Interpolation::Linear,
+            Extrapolation::Clip,
+        )
+        .unwrap();
+        assert_eq!(Source::<f64>::output(&thrust, 0.25), 50.0);
+        assert_eq!(Source::<f64>::output(&thrust, 1.5), 60.0);
+        assert_eq!(Source::<f64>::output(&thrust, 3.0), 40.0);
+
+        let table = FromTable::new(
+            Vector::from(vec![0.0, 1.0]),
+            Matrix::from(vec![vec![0.0, 1.0], vec![2.0, -1.0]]),
+            Interpolation::Nearest,
+            Extrapolation::Error,
+        )
+        .unwrap();
+        let v: Vector = table.output(0.7);
+        assert_eq!(v, Vector::from(vec![2.0, -1.0]));
+        let v: Vector = table.output(1.5);
+        assert!(v.has_nan());
+        assert_eq!(
+            table.sample(0.5, 2),
+            Err(SimError::OutOfRange {
+                value: 2.0,
+                min: 0.0,
+                max: 1.0
+            })
+        );
+
+        let spline = FromTable::from_pairs(
+            &[(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)],
+            Interpolation::CubicSpline,
+            Extrapolation::Clip,
+        )
+        .unwrap();
+        assert_eq!(spline.sample(1.0, 0), Ok(1.0));
+        assert!(spline.sample(0.5, 0).unwrap() > 0.5);
+    }
+}
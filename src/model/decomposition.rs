@@ -0,0 +1,850 @@
+use super::{Matrix, Vector};
+use crate::utils::SimError;
+
+const MAX_SWEEPS: usize = 100;
+
+fn max_abs(m: &Matrix) -> f64 {
+    m.data().iter().fold(0.0, |acc, x| acc.max(x.abs()))
+}
+
+fn check_symmetric(m: &Matrix) -> Result<usize, SimError> {
+    let n = m.check_square()?;
+    let tolerance = 1e-10 * max_abs(m);
+    for i in 0..n {
+        for j in 0..i {
+            if (m[(i, j)] - m[(j, i)]).abs() > tolerance {
+                return Err(SimError::InvalidParameter(
+                    "matrix is not symmetric".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(n)
+}
+
+// P A = L U with partial pivoting. Both factors are packed in one matrix,
+// with the unit lower factor below the diagonal.
+#[derive(Debug, Clone)]
+pub struct Lu {
+    lu: Matrix,
+    perm: Vec<usize>,
+    sign: f64,
+    singular: bool,
+}
+
+impl Lu {
+    pub fn l(&self) -> Matrix {
+        let n = self.perm.len();
+        Matrix::from_fn((n, n), |i, j| match i.cmp(&j) {
+            std::cmp::Ordering::Greater => self.lu[(i, j)],
+            std::cmp::Ordering::Equal => 1.0,
+            std::cmp::Ordering::Less => 0.0,
+        })
+    }
+
+    pub fn u(&self) -> Matrix {
+        let n = self.perm.len();
+        Matrix::from_fn((n, n), |i, j| if i <= j { self.lu[(i, j)] } else { 0.0 })
+    }
+
+    // Row `i` of `P A` is row `permutation()[i]` of `A`.
+    pub fn permutation(&self) -> &[usize] {
+        &self.perm
+    }
+
+    // True when a pivot is negligible next to the largest entry of `A`.
+    pub fn is_singular(&self) -> bool {
+        self.singular
+    }
+
+    pub fn det(&self) -> f64 {
+        self.sign
+            * (0..self.perm.len())
+                .map(|i| self.lu[(i, i)])
+                .product::<f64>()
+    }
+
+    pub fn solve(&self, b: &Vector) -> Result<Vector, SimError> {
+        let n = self.perm.len();
+        if b.dim() != n {
+            return Err(SimError::DimensionMismatch {
+                expected: n,
+                found: b.dim(),
+            });
+        }
+        if self.singular {
+            return Err(SimError::SingularMatrix);
+        }
+        Ok(Vector::from(self.substitute(b.data())))
+    }
+
+    pub fn inverse(&self) -> Result<Matrix, SimError> {
+        if self.singular {
+            return Err(SimError::SingularMatrix);
+        }
+        let n = self.perm.len();
+        let columns = (0..n)
+            .map(|j| {
+                let mut e = vec![0.0; n];
+                e[j] = 1.0;
+                self.substitute(&e)
+            })
+            .collect::<Vec<_>>();
+        Ok(Matrix::from_fn((n, n), |i, j| columns[j][i]))
+    }
+
+    fn substitute(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.perm.len();
+        let mut x = self.perm.iter().map(|p| b[*p]).collect::<Vec<_>>();
+        for i in 0..n {
+            for j in 0..i {
+                x[i] -= self.lu[(i, j)] * x[j];
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                x[i] -= self.lu[(i, j)] * x[j];
+            }
+            x[i] /= self.lu[(i, i)];
+        }
+        x
+    }
+}
+
+// A = Q R with Q orthogonal (m x m) and R upper triangular (m x n), built
+// from Householder reflections.
+#[derive(Debug, Clone)]
+pub struct Qr {
+    q: Matrix,
+    r: Matrix,
+}
+
+impl Qr {
+    pub fn q(&self) -> &Matrix {
+        &self.q
+    }
+
+    pub fn r(&self) -> &Matrix {
+        &self.r
+    }
+}
+
+// A = L L^T for a symmetric positive definite A.
+#[derive(Debug, Clone)]
+pub struct Cholesky {
+    l: Matrix,
+}
+
+impl Cholesky {
+    pub fn l(&self) -> &Matrix {
+        &self.l
+    }
+
+    pub fn solve(&self, b: &Vector) -> Result<Vector, SimError> {
+        let n = self.l.dim();
+        if b.dim() != n {
+            return Err(SimError::DimensionMismatch {
+                expected: n,
+                found: b.dim(),
+            });
+        }
+        let mut x = b.data().to_vec();
+        for i in 0..n {
+            for j in 0..i {
+                x[i] -= self.l[(i, j)] * x[j];
+            }
+            x[i] /= self.l[(i, i)];
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                x[i] -= self.l[(j, i)] * x[j];
+            }
+            x[i] /= self.l[(i, i)];
+        }
+        Ok(Vector::from(x))
+    }
+}
+
+// Eigenvalues in ascending order, with the matching orthonormal eigenvectors
+// as the columns of `eigenvectors`.
+#[derive(Debug, Clone)]
+pub struct SymmetricEigen {
+    eigenvalues: Vector,
+    eigenvectors: Matrix,
+}
+
+impl SymmetricEigen {
+    pub fn eigenvalues(&self) -> &Vector {
+        &self.eigenvalues
+    }
+
+    pub fn eigenvectors(&self) -> &Matrix {
+        &self.eigenvectors
+    }
+}
+
+// Eigenvalues of a general real matrix, split into real and imaginary parts.
+// Complex pairs appear next to each other.
+#[derive(Debug, Clone)]
+pub struct Eigenvalues {
+    real: Vector,
+    imag: Vector,
+}
+
+impl Eigenvalues {
+    pub fn real(&self) -> &Vector {
+        &self.real
+    }
+
+    pub fn imag(&self) -> &Vector {
+        &self.imag
+    }
+
+    // Largest real part, which decides the stability of `dx/dt = A x`.
+    pub fn spectral_abscissa(&self) -> f64 {
+        self.real
+            .data()
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+}
+
+// Thin SVD A = U diag(s) V^T with k = min(m, n) singular values in
+// descending order, U m x k and V n x k.
+#[derive(Debug, Clone)]
+pub struct Svd {
+    u: Matrix,
+    singular_values: Vector,
+    v: Matrix,
+}
+
+impl Svd {
+    pub fn u(&self) -> &Matrix {
+        &self.u
+    }
+
+    pub fn singular_values(&self) -> &Vector {
+        &self.singular_values
+    }
+
+    pub fn v(&self) -> &Matrix {
+        &self.v
+    }
+
+    // Number of singular values above `max(m, n) * eps * s_max`.
+    pub fn rank(&self) -> usize {
+        let s = self.singular_values.data();
+        let (m, n) = (self.u.dim(), self.v.dim());
+        let tolerance = m.max(n) as f64 * f64::EPSILON * s.first().copied().unwrap_or(0.0);
+        s.iter().filter(|s| **s > tolerance).count()
+    }
+
+    pub fn condition_number(&self) -> f64 {
+        let s = self.singular_values.data();
+        match (s.first(), s.last()) {
+            (Some(max), Some(min)) if *min > 0.0 => max / min,
+            _ => f64::INFINITY,
+        }
+    }
+}
+
+impl Matrix {
+    pub fn lu(&self) -> Result<Lu, SimError> {
+        let n = self.check_square()?;
+        let mut lu = self.clone();
+        let mut perm = (0..n).collect::<Vec<_>>();
+        let mut sign = 1.0;
+        let scale = max_abs(self);
+        let tolerance = f64::EPSILON * n as f64 * scale;
+        let mut singular = scale == 0.0;
+        for k in 0..n {
+            let p = (k..n)
+                .max_by(|a, b| lu[(*a, k)].abs().total_cmp(&lu[(*b, k)].abs()))
+                .unwrap_or(k);
+            if p != k {
+                lu.swap_rows(p, k);
+                perm.swap(p, k);
+                sign = -sign;
+            }
+            let pivot = lu[(k, k)];
+            if pivot.abs() <= tolerance {
+                singular = true;
+                continue;
+            }
+            for i in k + 1..n {
+                let factor = lu[(i, k)] / pivot;
+                lu[(i, k)] = factor;
+                for j in k + 1..n {
+                    lu[(i, j)] -= factor * lu[(k, j)];
+                }
+            }
+        }
+        Ok(Lu {
+            lu,
+            perm,
+            sign,
+            singular,
+        })
+    }
+
+    pub fn qr(&self) -> Result<Qr, SimError> {
+        let (m, n) = self.try_shape()?;
+        let mut r = self.clone();
+        let mut q = Matrix::identity(m);
+        for k in 0..n.min(m.saturating_sub(1)) {
+            let norm = (k..m).map(|i| r[(i, k)].powi(2)).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                continue;
+            }
+            let alpha = if r[(k, k)] > 0.0 { -norm } else { norm };
+            let mut v = (k..m).map(|i| r[(i, k)]).collect::<Vec<_>>();
+            v[0] -= alpha;
+            let v_norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+            v.iter_mut().for_each(|x| *x /= v_norm);
+            // R <- H R and Q <- Q H with H = I - 2 v v^T on rows k..m.
+            for j in 0..n {
+                let d = (k..m).map(|i| v[i - k] * r[(i, j)]).sum::<f64>();
+                (k..m).for_each(|i| r[(i, j)] -= 2.0 * v[i - k] * d);
+            }
+            for i in 0..m {
+                let d = (k..m).map(|j| q[(i, j)] * v[j - k]).sum::<f64>();
+                (k..m).for_each(|j| q[(i, j)] -= 2.0 * d * v[j - k]);
+            }
+            (k + 1..m).for_each(|i| r[(i, k)] = 0.0);
+        }
+        Ok(Qr { q, r })
+    }
+
+    pub fn cholesky(&self) -> Result<Cholesky, SimError> {
+        let n = check_symmetric(self)?;
+        let mut l = Matrix::zero((n, n));
+        for j in 0..n {
+            let d = self[(j, j)] - (0..j).map(|k| l[(j, k)].powi(2)).sum::<f64>();
+            if d <= 0.0 {
+                return Err(SimError::InvalidParameter(
+                    "matrix is not positive definite".to_string(),
+                ));
+            }
+            l[(j, j)] = d.sqrt();
+            for i in j + 1..n {
+                let s = self[(i, j)] - (0..j).map(|k| l[(i, k)] * l[(j, k)]).sum::<f64>();
+                l[(i, j)] = s / l[(j, j)];
+            }
+        }
+        Ok(Cholesky { l })
+    }
+
+    // Cyclic Jacobi rotations.
+    pub fn symmetric_eigen(&self) -> Result<SymmetricEigen, SimError> {
+        let n = check_symmetric(self)?;
+        let mut a = self.clone();
+        let mut v = Matrix::identity(n);
+        let scale = max_abs(self);
+        let mut converged = false;
+        for _ in 0..MAX_SWEEPS {
+            let off = (0..n)
+                .flat_map(|i| (0..n).filter(move |j| *j != i).map(move |j| (i, j)))
+                .map(|(i, j)| a[(i, j)].powi(2))
+                .sum::<f64>();
+            if off.sqrt() <= f64::EPSILON * scale {
+                converged = true;
+                break;
+            }
+            for p in 0..n {
+                for q in p + 1..n {
+                    if a[(p, q)] == 0.0 {
+                        continue;
+                    }
+                    let theta = (a[(q, q)] - a[(p, p)]) / (2.0 * a[(p, q)]);
+                    let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                    let c = 1.0 / (t * t + 1.0).sqrt();
+                    let s = t * c;
+                    for k in 0..n {
+                        let (akp, akq) = (a[(k, p)], a[(k, q)]);
+                        a[(k, p)] = c * akp - s * akq;
+                        a[(k, q)] = s * akp + c * akq;
+                    }
+                    for k in 0..n {
+                        let (apk, aqk) = (a[(p, k)], a[(q, k)]);
+                        a[(p, k)] = c * apk - s * aqk;
+                        a[(q, k)] = s * apk + c * aqk;
+                    }
+                    for k in 0..n {
+                        let (vkp, vkq) = (v[(k, p)], v[(k, q)]);
+                        v[(k, p)] = c * vkp - s * vkq;
+                        v[(k, q)] = s * vkp + c * vkq;
+                    }
+                }
+            }
+        }
+        if !converged {
+            return Err(SimError::SolverFailure(
+                "Jacobi eigenvalue iteration did not converge".to_string(),
+            ));
+        }
+        let mut order = (0..n).collect::<Vec<_>>();
+        order.sort_by(|i, j| a[(*i, *i)].total_cmp(&a[(*j, *j)]));
+        Ok(SymmetricEigen {
+            eigenvalues: Vector::from(order.iter().map(|i| a[(*i, *i)]).collect::<Vec<_>>()),
+            eigenvectors: Matrix::from_fn((n, n), |i, j| v[(i, order[j])]),
+        })
+    }
+
+    // Reduction to Hessenberg form followed by the shifted QR algorithm
+    // with Francis double steps.
+    pub fn eigenvalues(&self) -> Result<Eigenvalues, SimError> {
+        let n = self.check_square()?;
+        let mut a = self.clone();
+        hessenberg(&mut a);
+        let (real, imag) = hqr(&mut a, n)?;
+        Ok(Eigenvalues {
+            real: Vector::from(real),
+            imag: Vector::from(imag),
+        })
+    }
+
+    // One-sided Jacobi on the columns, on the transpose for wide matrices.
+    pub fn svd(&self) -> Result<Svd, SimError> {
+        let (m, n) = self.try_shape()?;
+        if m < n {
+            let svd = self.transpose().svd()?;
+            return Ok(Svd {
+                u: svd.v,
+                singular_values: svd.singular_values,
+                v: svd.u,
+            });
+        }
+        let mut u = self.clone();
+        let mut v = Matrix::identity(n);
+        let mut converged = false;
+        for _ in 0..MAX_SWEEPS {
+            let mut rotated = false;
+            for p in 0..n {
+                for q in p + 1..n {
+                    let alpha = (0..m).map(|i| u[(i, p)].powi(2)).sum::<f64>();
+                    let beta = (0..m).map(|i| u[(i, q)].powi(2)).sum::<f64>();
+                    let gamma = (0..m).map(|i| u[(i, p)] * u[(i, q)]).sum::<f64>();
+                    if gamma.abs() <= f64::EPSILON * (alpha * beta).sqrt() || gamma == 0.0 {
+                        continue;
+                    }
+                    rotated = true;
+                    let zeta = (beta - alpha) / (2.0 * gamma);
+                    let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                    let c = 1.0 / (1.0 + t * t).sqrt();
+                    let s = c * t;
+                    for i in 0..m {
+                        let (up, uq) = (u[(i, p)], u[(i, q)]);
+                        u[(i, p)] = c * up - s * uq;
+                        u[(i, q)] = s * up + c * uq;
+                    }
+                    for i in 0..n {
+                        let (vp, vq) = (v[(i, p)], v[(i, q)]);
+                        v[(i, p)] = c * vp - s * vq;
+                        v[(i, q)] = s * vp + c * vq;
+                    }
+                }
+            }
+            if !rotated {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            return Err(SimError::SolverFailure(
+                "Jacobi SVD iteration did not converge".to_string(),
+            ));
+        }
+        let norms = (0..n)
+            .map(|j| (0..m).map(|i| u[(i, j)].powi(2)).sum::<f64>().sqrt())
+            .collect::<Vec<_>>();
+        let mut order = (0..n).collect::<Vec<_>>();
+        order.sort_by(|i, j| norms[*j].total_cmp(&norms[*i]));
+        Ok(Svd {
+            u: Matrix::from_fn((m, n), |i, j| {
+                let s = norms[order[j]];
+                if s > 0.0 {
+                    u[(i, order[j])] / s
+                } else {
+                    0.0
+                }
+            }),
+            singular_values: Vector::from(order.iter().map(|j| norms[*j]).collect::<Vec<_>>()),
+            v: Matrix::from_fn((n, n), |i, j| v[(i, order[j])]),
+        })
+    }
+
+    pub fn rank(&self) -> Result<usize, SimError> {
+        Ok(self.svd()?.rank())
+    }
+
+    pub fn condition_number(&self) -> Result<f64, SimError> {
+        Ok(self.svd()?.condition_number())
+    }
+}
+
+// The eigenvalue routines below follow Golub & Van Loan, Matrix Computations,
+// 4th ed., sections 7.4 and 7.5: Householder reduction to Hessenberg form
+// (Algorithm 7.4.2), then implicit double-shift QR steps (Algorithm 7.5.1) on
+// the unreduced trailing block until its last one or two rows split off.
+
+const MAX_QR_ITERATIONS: usize = 30;
+
+// Householder vector `v` and `beta` such that (I - beta v v^T) x is a multiple
+// of the first unit vector. The sign of the target is chosen opposite to x[0]
+// so that forming v[0] does not cancel.
+fn householder(x: &[f64]) -> (Vec<f64>, f64) {
+    let norm = x.iter().map(|v| v * v).sum::<f64>().sqrt();
+    let mut v = x.to_vec();
+    if norm == 0.0 {
+        return (v, 0.0);
+    }
+    v[0] += norm.copysign(x[0]);
+    let vv = v.iter().map(|v| v * v).sum::<f64>();
+    (v, 2.0 / vv)
+}
+
+// Applies I - beta v v^T to rows `r..r + v.len()` of the given columns.
+fn reflect_rows(a: &mut Matrix, v: &[f64], beta: f64, r: usize, cols: std::ops::Range<usize>) {
+    for j in cols {
+        let s = beta * (0..v.len()).map(|i| v[i] * a[(r + i, j)]).sum::<f64>();
+        for (i, vi) in v.iter().enumerate() {
+            a[(r + i, j)] -= s * vi;
+        }
+    }
+}
+
+// Applies I - beta v v^T to columns `c..c + v.len()` of the given rows.
+fn reflect_columns(a: &mut Matrix, v: &[f64], beta: f64, c: usize, rows: std::ops::Range<usize>) {
+    for i in rows {
+        let s = beta * (0..v.len()).map(|j| a[(i, c + j)] * v[j]).sum::<f64>();
+        for (j, vj) in v.iter().enumerate() {
+            a[(i, c + j)] -= s * vj;
+        }
+    }
+}
+
+// Orthogonal similarity to upper Hessenberg form: column k is reduced below
+// the subdiagonal by a reflection acting on rows and columns k + 1 onwards.
+fn hessenberg(a: &mut Matrix) {
+    let n = a.dim();
+    for k in 0..n.saturating_sub(2) {
+        let x = (k + 1..n).map(|i| a[(i, k)]).collect::<Vec<_>>();
+        let (v, beta) = householder(&x);
+        if beta == 0.0 {
+            continue;
+        }
+        reflect_rows(a, &v, beta, k + 1, k..n);
+        reflect_columns(a, &v, beta, k + 1, 0..n);
+        for i in k + 2..n {
+            a[(i, k)] = 0.0;
+        }
+    }
+}
+
+// Eigenvalues of the 2 x 2 block [[a, b], [c, d]] as (real, imag) pairs. The
+// larger root in magnitude is formed first and the other from the product
+// to avoid cancellation.
+fn eigenvalues_2x2(a: f64, b: f64, c: f64, d: f64) -> [(f64, f64); 2] {
+    let p = 0.5 * (a - d);
+    let q = p * p + b * c;
+    if q >= 0.0 {
+        let s = p + q.sqrt().copysign(p);
+        let other = if s != 0.0 { d - b * c / s } else { d };
+        [(d + s, 0.0), (other, 0.0)]
+    } else {
+        let im = (-q).sqrt();
+        [(d + p, im), (d + p, -im)]
+    }
+}
+
+// One Francis double-shift step on the unreduced block `lo..hi`, with the
+// shifts given by their sum `s` and product `t`. The first column of
+// (H - a I)(H - b I) is introduced by a reflection and the resulting bulge is
+// chased down the subdiagonal.
+fn francis_step(h: &mut Matrix, lo: usize, hi: usize, s: f64, t: f64) {
+    let mut x = h[(lo, lo)] * h[(lo, lo)] + h[(lo, lo + 1)] * h[(lo + 1, lo)] - s * h[(lo, lo)] + t;
+    let mut y = h[(lo + 1, lo)] * (h[(lo, lo)] + h[(lo + 1, lo + 1)] - s);
+    let mut z = h[(lo + 1, lo)] * h[(lo + 2, lo + 1)];
+    for k in lo..hi - 2 {
+        let (v, beta) = householder(&[x, y, z]);
+        let first = if k == lo { lo } else { k - 1 };
+        reflect_rows(h, &v, beta, k, first..hi);
+        reflect_columns(h, &v, beta, k, lo..(k + 4).min(hi));
+        if k > lo {
+            h[(k + 1, k - 1)] = 0.0;
+            h[(k + 2, k - 1)] = 0.0;
+        }
+        x = h[(k + 1, k)];
+        y = h[(k + 2, k)];
+        if k + 3 < hi {
+            z = h[(k + 3, k)];
+        }
+    }
+    let (v, beta) = householder(&[x, y]);
+    reflect_rows(h, &v, beta, hi - 2, hi - 3..hi);
+    reflect_columns(h, &v, beta, hi - 2, lo..hi);
+    h[(hi - 1, hi - 3)] = 0.0;
+}
+
+// Eigenvalues of an upper Hessenberg matrix, which is destroyed. Subdiagonal
+// entries below eps times their neighbouring diagonal are set to zero, and
+// the trailing block is deflated once it is 1 x 1 or 2 x 2.
+fn hqr(h: &mut Matrix, n: usize) -> Result<(Vec<f64>, Vec<f64>), SimError> {
+    let (mut wr, mut wi) = (vec![0.0; n], vec![0.0; n]);
+    let norm = max_abs(h);
+    let mut hi = n;
+    let mut its = 0;
+    while hi > 0 {
+        let mut lo = hi - 1;
+        while lo > 0 {
+            let mut scale = h[(lo - 1, lo - 1)].abs() + h[(lo, lo)].abs();
+            if scale == 0.0 {
+                scale = norm;
+            }
+            if h[(lo, lo - 1)].abs() <= f64::EPSILON * scale {
+                h[(lo, lo - 1)] = 0.0;
+                break;
+            }
+            lo -= 1;
+        }
+        match hi - lo {
+            1 => {
+                wr[hi - 1] = h[(hi - 1, hi - 1)];
+                hi -= 1;
+                its = 0;
+            }
+            2 => {
+                let (a, b) = (h[(lo, lo)], h[(lo, lo + 1)]);
+                let (c, d) = (h[(lo + 1, lo)], h[(lo + 1, lo + 1)]);
+                for (i, (re, im)) in eigenvalues_2x2(a, b, c, d).into_iter().enumerate() {
+                    wr[lo + i] = re;
+                    wi[lo + i] = im;
+                }
+                hi -= 2;
+                its = 0;
+            }
+            _ => {
+                if its == MAX_QR_ITERATIONS {
+                    return Err(SimError::SolverFailure(
+                        "QR eigenvalue iteration did not converge".to_string(),
+                    ));
+                }
+                its += 1;
+                let m = hi - 1;
+                let (s, t) = if its % 10 == 0 {
+                    // Ad hoc shifts break the cycles the Wilkinson-type
+                    // shifts can fall into.
+                    let w = h[(m, m - 1)].abs() + h[(m - 1, m - 2)].abs();
+                    (1.5 * w, w * w)
+                } else {
+                    (
+                        h[(m - 1, m - 1)] + h[(m, m)],
+                        h[(m - 1, m - 1)] * h[(m, m)] - h[(m - 1, m)] * h[(m, m - 1)],
+                    )
+                };
+                francis_step(h, lo, hi, s, t);
+            }
+        }
+    }
+    Ok((wr, wi))
+}
+
+#[cfg(test)]
+mod decomposition_tests {
+    use super::*;
+
+    fn assert_close(a: &Matrix, b: &Matrix, tolerance: f64) {
+        let (m, n) = a.shape();
+        assert_eq!((m, n), b.shape());
+        for i in 0..m {
+            for j in 0..n {
+                assert!(
+                    (a[(i, j)] - b[(i, j)]).abs() < tolerance,
+                    "{:?} != {:?}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    fn sorted(v: &Vector) -> Vec<f64> {
+        let mut v = v.data().to_vec();
+        v.sort_by(f64::total_cmp);
+        v
+    }
+
+    #[test]
+    fn test_lu_qr() {
+        let a = Matrix::from(vec![vec![2., 1., 1.], vec![4., -6., 0.], vec![-2., 7., 2.]]);
+        let lu = a.lu().unwrap();
+        assert_eq!(lu.permutation(), &[1, 2, 0]);
+        let pa = Matrix::from(
+            lu.permutation()
+                .iter()
+                .map(|p| a[*p].to_vec())
+                .collect::<Vec<_>>(),
+        );
+        assert_close(&lu.l().matmul(&lu.u()).unwrap(), &pa, 1e-12);
+        assert!((lu.det() + 16.0).abs() < 1e-12);
+
+        let tall = Matrix::from(vec![vec![12., -51.], vec![6., 167.], vec![-4., 24.]]);
+        let qr = tall.qr().unwrap();
+        assert_close(&qr.q().matmul(qr.r()).unwrap(), &tall, 1e-10);
+        assert_close(
+            &qr.q().transpose().matmul(qr.q()).unwrap(),
+            &Matrix::identity(3),
+            1e-12,
+        );
+        assert!((qr.r()[(0, 0)].abs() - 14.0).abs() < 1e-12);
+        assert!((qr.r()[(1, 1)].abs() - 175.0).abs() < 1e-10);
+        assert_eq!(qr.r()[(2, 1)], 0.0);
+    }
+
+    #[test]
+    fn test_cholesky() {
+        let a = Matrix::from(vec![
+            vec![4., 12., -16.],
+            vec![12., 37., -43.],
+            vec![-16., -43., 98.],
+        ]);
+        let chol = a.cholesky().unwrap();
+        let expected = Matrix::from(vec![vec![2., 0., 0.], vec![6., 1., 0.], vec![-8., 5., 3.]]);
+        assert_close(chol.l(), &expected, 1e-12);
+        let x = chol.solve(&Vector::from(vec![0., 6., 39.])).unwrap();
+        assert_close(
+            &Matrix::from(vec![x]),
+            &Matrix::from(vec![vec![1., 1., 1.]]),
+            1e-10,
+        );
+        let indefinite = Matrix::from(vec![vec![1., 2.], vec![2., 1.]]);
+        assert!(indefinite.cholesky().is_err());
+        let asymmetric = Matrix::from(vec![vec![1., 2.], vec![0., 1.]]);
+        assert!(asymmetric.cholesky().is_err());
+    }
+
+    #[test]
+    fn test_eigen() {
+        let a = Matrix::from(vec![
+            vec![2., -1., 0.],
+            vec![-1., 2., -1.],
+            vec![0., -1., 2.],
+        ]);
+        let eig = a.symmetric_eigen().unwrap();
+        let r = 2f64.sqrt();
+        let expected = [2. - r, 2., 2. + r];
+        for (l, e) in eig.eigenvalues().data().iter().zip(expected) {
+            assert!((l - e).abs() < 1e-12);
+        }
+        // A V = V diag(l)
+        let v = eig.eigenvectors();
+        let d = Matrix::from(
+            (0..3)
+                .map(|i| {
+                    (0..3)
+                        .map(|j| if i == j { expected[i] } else { 0.0 })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>(),
+        );
+        assert_close(&a.matmul(v).unwrap(), &v.matmul(&d).unwrap(), 1e-12);
+
+        // Companion matrix of (s + 1)(s + 2)(s + 3).
+        let companion = Matrix::from(vec![
+            vec![-6., -11., -6.],
+            vec![1., 0., 0.],
+            vec![0., 1., 0.],
+        ]);
+        let eig = companion.eigenvalues().unwrap();
+        let re = sorted(eig.real());
+        for (l, e) in re.iter().zip([-3., -2., -1.]) {
+            assert!((l - e).abs() < 1e-10);
+        }
+        assert!(eig.imag().data().iter().all(|x| *x == 0.0));
+        assert!((eig.spectral_abscissa() + 1.0).abs() < 1e-10);
+
+        // A rotation block with a real eigenvalue: 1 +- 2i and 3.
+        let mixed = Matrix::from(vec![
+            vec![1., -2., 0., 0.],
+            vec![2., 1., 0., 0.],
+            vec![0., 0., 3., 1.],
+            vec![0., 0., 0., 3.],
+        ]);
+        let eig = mixed.eigenvalues().unwrap();
+        let imag = sorted(eig.imag());
+        assert!((imag[0] + 2.0).abs() < 1e-10 && (imag[3] - 2.0).abs() < 1e-10);
+        let re = sorted(eig.real());
+        for (l, e) in re.iter().zip([1., 1., 3., 3.]) {
+            assert!((l - e).abs() < 1e-6);
+        }
+        assert!(Matrix::from(vec![vec![1., 2.]]).eigenvalues().is_err());
+
+        // The eigenvalues of a dense nonsymmetric matrix sum to its trace and
+        // multiply to its determinant.
+        let dense = Matrix::from_fn((6, 6), |i, j| {
+            ((3 * i + 7 * j) % 11) as f64 - 5.0 + (i == j) as u8 as f64
+        });
+        let eig = dense.eigenvalues().unwrap();
+        let sum = eig.real().data().iter().sum::<f64>();
+        assert!((sum - dense.trace().unwrap()).abs() < 1e-9);
+        let (mut re, mut im) = (1.0, 0.0);
+        for (a, b) in eig.real().data().iter().zip(eig.imag().data()) {
+            (re, im) = (re * a - im * b, re * b + im * a);
+        }
+        let det = dense.det().unwrap();
+        assert!(
+            (re - det).abs() < 1e-8 * det.abs().max(1.0) && im.abs() < 1e-8 * det.abs().max(1.0)
+        );
+
+        // A cyclic permutation stalls the standard shifts until the ad hoc
+        // ones break the symmetry; its eigenvalues are the cube roots of 1.
+        let cycle = Matrix::from(vec![vec![0., 0., 1.], vec![1., 0., 0.], vec![0., 1., 0.]]);
+        let eig = cycle.eigenvalues().unwrap();
+        let re = sorted(eig.real());
+        for (l, e) in re.iter().zip([-0.5, -0.5, 1.0]) {
+            assert!((l - e).abs() < 1e-10);
+        }
+        let imag = sorted(eig.imag());
+        assert!((imag[2] - 0.75f64.sqrt()).abs() < 1e-10);
+
+        // Larger dense case, checked against trace(A) and trace(A^2).
+        let big = Matrix::from_fn((20, 20), |i, j| {
+            ((i * 31 + j * 17 + i * j) % 23) as f64 - 11.0
+        });
+        let eig = big.eigenvalues().unwrap();
+        let sum = eig.real().data().iter().sum::<f64>();
+        assert!((sum - big.trace().unwrap()).abs() < 1e-9);
+        let squares = eig
+            .real()
+            .data()
+            .iter()
+            .zip(eig.imag().data())
+            .map(|(a, b)| a * a - b * b)
+            .sum::<f64>();
+        let trace2 = big.matmul(&big).unwrap().trace().unwrap();
+        assert!((squares - trace2).abs() < 1e-8 * trace2.abs());
+    }
+
+    #[test]
+    fn test_svd() {
+        let a = Matrix::from(vec![vec![3., 2., 2.], vec![2., 3., -2.]]);
+        let svd = a.svd().unwrap();
+        let s = svd.singular_values();
+        assert!((s[0] - 5.0).abs() < 1e-12 && (s[1] - 3.0).abs() < 1e-12);
+        let sigma = Matrix::from(vec![vec![s[0], 0.], vec![0., s[1]]]);
+        let rebuilt = svd
+            .u()
+            .matmul(&sigma)
+            .unwrap()
+            .matmul(&svd.v().transpose())
+            .unwrap();
+        assert_close(&rebuilt, &a, 1e-12);
+        assert_eq!(svd.rank(), 2);
+        assert!((svd.condition_number() - 5.0 / 3.0).abs() < 1e-12);
+
+        let rank_one = Matrix::from(vec![vec![1., 2.], vec![2., 4.], vec![3., 6.]]);
+        assert_eq!(rank_one.rank(), Ok(1));
+        assert!(rank_one.condition_number().unwrap() > 1e12);
+    }
+}
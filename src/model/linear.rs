@@ -0,0 +1,93 @@
+use super::Matrix;
+use crate::utils::SimError;
+
+pub(crate) fn shape(m: &Matrix) -> (usize, usize) {
+    m.try_shape().unwrap_or((0, 0))
+}
+
+fn check_shape(m: &Matrix, expected: (usize, usize)) -> Result<(), SimError> {
+    let (rows, cols) = shape(m);
+    if rows != expected.0 && !(rows == 0 && expected.1 == 0) {
+        return Err(SimError::DimensionMismatch {
+            expected: expected.0,
+            found: rows,
+        });
+    }
+    if rows > 0 && cols != expected.1 {
+        return Err(SimError::DimensionMismatch {
+            expected: expected.1,
+            found: cols,
+        });
+    }
+    Ok(())
+}
+
+// Checks that (A, B, C, D) fit together and returns the number of states,
+// inputs and outputs.
+pub(crate) fn check_realization(
+    a: &Matrix,
+    b: &Matrix,
+    c: &Matrix,
+    d: &Matrix,
+) -> Result<(usize, usize, usize), SimError> {
+    let (p, m) = d.try_shape()?;
+    let (n, _) = shape(a);
+    check_shape(a, (n, n))?;
+    check_shape(b, (n, m))?;
+    check_shape(c, (p, n))?;
+    check_shape(d, (p, m))?;
+    Ok((n, m, p))
+}
+
+// `m * x + n * u`, for matrices that may have no columns.
+pub(crate) fn affine(m: &Matrix, x: &[f64], n: &Matrix, u: &[f64], rows: usize) -> Vec<f64> {
+    (0..rows)
+        .map(|i| {
+            let mx = (0..x.len()).map(|j| m[(i, j)] * x[j]).sum::<f64>();
+            let nu = (0..u.len()).map(|j| n[(i, j)] * u[j]).sum::<f64>();
+            mx + nu
+        })
+        .collect()
+}
+
+// Controllable canonical form of `num / den` with coefficients in
+// descending powers. Returns (A, B, C, D) for a single input and output.
+pub(crate) fn controllable_canonical(
+    num: &[f64],
+    den: &[f64],
+) -> Result<(Matrix, Matrix, Matrix, Matrix), SimError> {
+    let den = match den.iter().position(|a| *a != 0.0) {
+        Some(first) => &den[first..],
+        None => {
+            return Err(SimError::InvalidParameter(
+                "denominator must not be zero".to_string(),
+            ))
+        }
+    };
+    let num = &num[num.iter().position(|b| *b != 0.0).unwrap_or(num.len())..];
+    let n = den.len() - 1;
+    if num.len() > den.len() {
+        return Err(SimError::InvalidParameter(
+            "transfer function must be proper".to_string(),
+        ));
+    }
+    let a0 = den[0];
+    let a = den.iter().map(|a| a / a0).collect::<Vec<_>>();
+    let mut b = vec![0.0; n + 1 - num.len()];
+    b.extend(num.iter().map(|b| b / a0));
+
+    let rows = |r: usize, c: usize, f: &dyn Fn(usize, usize) -> f64| Matrix::from_fn((r, c), f);
+    let a_mat = rows(n, n, &|i, j| {
+        if i == 0 {
+            -a[j + 1]
+        } else if j + 1 == i {
+            1.0
+        } else {
+            0.0
+        }
+    });
+    let b_mat = rows(n, 1, &|i, _| if i == 0 { 1.0 } else { 0.0 });
+    let c_mat = rows(1, n, &|_, j| b[j + 1] - b[0] * a[j + 1]);
+    let d_mat = rows(1, 1, &|_, _| b[0]);
+    Ok((a_mat, b_mat, c_mat, d_mat))
+}
@@ -1,48 +1,222 @@
 use super::{vector::Vector, IsValue};
-use rayon::prelude::*;
-use std::{
-    ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Sub, SubAssign},
-    ops::{RangeFrom, RangeTo},
-    sync::{Arc, Mutex},
+use crate::utils::SimError;
+use std::ops::{
+    Add, AddAssign, Bound, Div, DivAssign, Index, IndexMut, Mul, MulAssign, RangeBounds, RangeFrom,
+    RangeTo, Sub, SubAssign,
 };
 
-#[derive(Debug, Clone)]
+// Row-major storage: element (i, j) lives at `data[i * cols + j]`, so rows
+// are contiguous slices and columns are strided views over the same buffer.
+#[derive(Debug, Clone, Default)]
 pub struct Matrix {
-    dim: usize,
-    data: Vec<Vector>,
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
 }
 
 impl Matrix {
     pub fn new(shape: (usize, usize)) -> Self {
         Matrix {
-            dim: shape.0,
-            data: vec![Vector::new(shape.1); shape.0],
+            rows: shape.0,
+            cols: shape.1,
+            data: vec![0.0; shape.0 * shape.1],
         }
     }
 
+    pub fn from_fn(shape: (usize, usize), f: impl Fn(usize, usize) -> f64) -> Self {
+        let (rows, cols) = shape;
+        Matrix {
+            rows,
+            cols,
+            data: (0..rows * cols).map(|k| f(k / cols, k % cols)).collect(),
+        }
+    }
+
+    pub fn try_from_vec(shape: (usize, usize), data: Vec<f64>) -> Result<Self, SimError> {
+        if data.len() != shape.0 * shape.1 {
+            return Err(SimError::DimensionMismatch {
+                expected: shape.0 * shape.1,
+                found: data.len(),
+            });
+        }
+        Ok(Matrix {
+            rows: shape.0,
+            cols: shape.1,
+            data,
+        })
+    }
+
     pub fn dim(&self) -> usize {
-        self.dim
+        self.rows
     }
 
-    pub fn data(&self) -> &[Vector] {
+    // The elements in row-major order.
+    pub fn data(&self) -> &[f64] {
         &self.data
     }
 
+    pub fn data_mut(&mut self) -> &mut [f64] {
+        &mut self.data
+    }
+
     pub fn shape(&self) -> (usize, usize) {
-        (self.dim, self.data[0].dim())
+        self.try_shape().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_shape(&self) -> Result<(usize, usize), SimError> {
+        match self.rows {
+            0 => Err(SimError::EmptyMatrix),
+            rows => Ok((rows, self.cols)),
+        }
+    }
+
+    pub fn try_from_rows(rows: Vec<Vector>) -> Result<Self, SimError> {
+        if rows.is_empty() {
+            return Err(SimError::EmptyMatrix);
+        }
+        Self::try_from_slices(rows.iter().map(|r| r.data()))
+    }
+
+    fn try_from_slices<'a>(
+        rows: impl ExactSizeIterator<Item = &'a [f64]>,
+    ) -> Result<Self, SimError> {
+        let n = rows.len();
+        let mut data = Vec::new();
+        let mut cols = None;
+        for row in rows {
+            let expected = *cols.get_or_insert(row.len());
+            if row.len() != expected {
+                return Err(SimError::DimensionMismatch {
+                    expected,
+                    found: row.len(),
+                });
+            }
+            data.extend_from_slice(row);
+        }
+        Ok(Matrix {
+            rows: n,
+            cols: cols.unwrap_or(0),
+            data,
+        })
+    }
+
+    #[inline]
+    pub fn row(&self, i: usize) -> &[f64] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    #[inline]
+    pub fn row_mut(&mut self, i: usize) -> &mut [f64] {
+        &mut self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    // The selected rows as one row-major slice.
+    pub fn rows(&self, range: impl RangeBounds<usize>) -> &[f64] {
+        let start = match range.start_bound() {
+            Bound::Included(&i) => i,
+            Bound::Excluded(&i) => i + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&i) => i + 1,
+            Bound::Excluded(&i) => i,
+            Bound::Unbounded => self.rows,
+        };
+        &self.data[start * self.cols..end * self.cols]
+    }
+
+    #[inline]
+    fn offset(&self, (i, j): (usize, usize)) -> usize {
+        assert!(
+            j < self.cols,
+            "column {} out of range for {} columns",
+            j,
+            self.cols
+        );
+        i * self.cols + j
+    }
+
+    pub fn column(&self, j: usize) -> Column<'_> {
+        assert!(
+            j < self.cols,
+            "column {} out of range for {} columns",
+            j,
+            self.cols
+        );
+        Column {
+            data: &self.data[j..],
+            stride: self.cols,
+            len: self.rows,
+        }
+    }
+
+    pub fn iter_rows(&self) -> impl ExactSizeIterator<Item = &[f64]> + '_ {
+        (0..self.rows).map(|i| self.row(i))
+    }
+
+    pub fn iter_columns(&self) -> impl ExactSizeIterator<Item = Column<'_>> + '_ {
+        (0..self.cols).map(|j| self.column(j))
+    }
+
+    pub fn try_add(&self, other: &Self) -> Result<Self, SimError> {
+        self.check_shape(other)?;
+        Ok(self.clone() + other.clone())
+    }
+
+    pub fn try_sub(&self, other: &Self) -> Result<Self, SimError> {
+        self.check_shape(other)?;
+        Ok(self.clone() - other.clone())
+    }
+
+    pub fn try_add_vector(&self, other: &Vector) -> Result<Self, SimError> {
+        self.check_cols(other)?;
+        Ok(self.clone() + other.clone())
+    }
+
+    pub fn try_sub_vector(&self, other: &Vector) -> Result<Self, SimError> {
+        self.check_cols(other)?;
+        Ok(self.clone() - other.clone())
+    }
+
+    pub fn try_mean(&self) -> Result<Vector, SimError> {
+        let (rows, _) = self.try_shape()?;
+        Ok(self.sum() / rows as f64)
+    }
+
+    pub fn has_nan(&self) -> bool {
+        self.data.iter().any(|x| x.is_nan())
+    }
+
+    fn check_shape(&self, other: &Self) -> Result<(), SimError> {
+        let expected = self.try_shape()?;
+        let found = other.try_shape()?;
+        if expected != found {
+            return Err(SimError::ShapeMismatch { expected, found });
+        }
+        Ok(())
+    }
+
+    fn check_cols(&self, other: &Vector) -> Result<(), SimError> {
+        let (_, cols) = self.try_shape()?;
+        if cols != other.dim() {
+            return Err(SimError::DimensionMismatch {
+                expected: cols,
+                found: other.dim(),
+            });
+        }
+        Ok(())
     }
 
     pub fn dim_eq(&self, other: &Self) -> bool {
-        self.dim == other.dim
+        self.rows == other.rows
     }
 
     pub fn shape_eq(&self, other: &Self) -> bool {
         self.shape() == other.shape()
     }
 
-    pub fn fill(&self, value: f64) {
-        let mut vec = self.clone();
-        vec.data.par_iter_mut().for_each(|x| x.fill(value));
+    pub fn fill(&mut self, value: f64) {
+        self.data.fill(value);
     }
 
     pub fn zero(dim: (usize, usize)) -> Self {
@@ -54,7 +228,7 @@ impl Matrix {
     }
 
     pub fn ones(dim: (usize, usize)) -> Self {
-        let matrix = Self::new(dim);
+        let mut matrix = Self::new(dim);
         matrix.fill(1.0);
         matrix
     }
@@ -63,24 +237,18 @@ impl Matrix {
         Self::ones(self.shape())
     }
 
-    pub fn ravel(&self) -> Vector {
-        let mut data = self.data.clone();
-        let mut new_data = Vec::new();
-        data.iter_mut()
-            .for_each(|x| new_data.extend(x.data().clone()));
-        Vector::from(new_data)
+    // The elements row by row, without copying.
+    pub fn ravel(&self) -> &[f64] {
+        &self.data
     }
 
+    // Column sums.
     pub fn sum(&self) -> Vector {
-        let matrix = self.clone();
-        let res = Vector::zero(self.shape().1);
-        let res_lock = Arc::new(Mutex::new(res));
-        matrix
-            .data
-            .par_iter()
-            .for_each(|x| *(res_lock.lock().unwrap()) += x.clone());
-        let new_data = Arc::try_unwrap(res_lock).unwrap().into_inner().unwrap();
-        Vector::from(new_data)
+        let mut res = vec![0.0; self.shape().1];
+        for row in self.iter_rows() {
+            res.iter_mut().zip(row).for_each(|(a, b)| *a += b);
+        }
+        Vector::from(res)
     }
 
     pub fn mean(&self) -> Vector {
@@ -88,226 +256,457 @@ impl Matrix {
     }
 
     pub fn last(&self) -> Option<Vector> {
-        match self.data.last() {
-            Some(x) => Some(Vector::from(x.clone())),
-            None => None,
-        }
+        self.rows.checked_sub(1).map(|i| Vector::from(self.row(i)))
     }
 
     pub fn linespace(start: &Vector, end: &Vector, n: usize) -> Self {
-        let mut data = Vec::with_capacity(n);
-        let mut i = 0;
-        while i < n {
-            data.push(start.clone() + (end.clone() - start.clone()) * (i as f64) / (n as f64));
-            i += 1;
+        let diff = end.clone() - start.clone();
+        Self::from_fn((n, start.dim()), |i, j| {
+            start[j] + diff[j] * (i as f64) / (n as f64)
+        })
+    }
+}
+
+// Strided view of one matrix column.
+#[derive(Debug, Clone, Copy)]
+pub struct Column<'a> {
+    data: &'a [f64],
+    stride: usize,
+    len: usize,
+}
+
+impl<'a> Column<'a> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> Option<&'a f64> {
+        if i < self.len {
+            self.data.get(i * self.stride)
+        } else {
+            None
         }
-        Self::from(data)
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &'a f64> + 'a {
+        self.data.iter().step_by(self.stride).take(self.len)
+    }
+
+    pub fn to_vector(self) -> Vector {
+        Vector::from(self.iter().copied().collect::<Vec<_>>())
     }
 }
 
-impl IsValue for Matrix {}
+impl Index<usize> for Column<'_> {
+    type Output = f64;
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index)
+            .unwrap_or_else(|| panic!("row {} out of range for {} rows", index, self.len))
+    }
+}
+
+impl Matrix {
+    pub fn identity(n: usize) -> Self {
+        let mut m = Self::zero((n, n));
+        for i in 0..n {
+            m[(i, i)] = 1.0;
+        }
+        m
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::from_fn((self.cols, self.rows), |i, j| self[(j, i)])
+    }
+
+    pub fn matmul(&self, other: &Self) -> Result<Self, SimError> {
+        let (rows, inner) = self.try_shape()?;
+        let (other_rows, cols) = other.try_shape()?;
+        if inner != other_rows {
+            return Err(SimError::DimensionMismatch {
+                expected: inner,
+                found: other_rows,
+            });
+        }
+        // i-k-j order so the inner loop runs along contiguous rows.
+        let mut res = Self::zero((rows, cols));
+        for i in 0..rows {
+            let out = &mut res.data[i * cols..(i + 1) * cols];
+            for (a, b) in self.row(i).iter().zip(other.iter_rows()) {
+                out.iter_mut().zip(b).for_each(|(o, b)| *o += a * b);
+            }
+        }
+        Ok(res)
+    }
 
+    pub fn matvec(&self, other: &Vector) -> Result<Vector, SimError> {
+        self.check_cols(other)?;
+        Ok(Vector::from(
+            self.iter_rows()
+                .map(|row| row.iter().zip(other.data()).map(|(a, b)| a * b).sum())
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    pub fn trace(&self) -> Result<f64, SimError> {
+        let n = self.check_square()?;
+        Ok((0..n).map(|i| self[(i, i)]).sum())
+    }
+
+    pub fn det(&self) -> Result<f64, SimError> {
+        Ok(self.lu()?.det())
+    }
+
+    pub fn inverse(&self) -> Result<Self, SimError> {
+        self.lu()?.inverse()
+    }
+
+    // Solves `self * x = b`.
+    pub fn solve(&self, b: &Vector) -> Result<Vector, SimError> {
+        self.lu()?.solve(b)
+    }
+
+    pub fn swap_rows(&mut self, i: usize, j: usize) {
+        if i == j {
+            return;
+        }
+        let (lo, hi) = (i.min(j), i.max(j));
+        let (head, tail) = self.data.split_at_mut(hi * self.cols);
+        head[lo * self.cols..(lo + 1) * self.cols].swap_with_slice(&mut tail[..self.cols]);
+    }
+
+    pub(crate) fn check_square(&self) -> Result<usize, SimError> {
+        let (rows, cols) = self.try_shape()?;
+        if rows != cols {
+            return Err(SimError::NotSquare { rows, cols });
+        }
+        Ok(rows)
+    }
+}
+
+impl IsValue for Matrix {
+    fn has_nan(&self) -> bool {
+        Matrix::has_nan(self)
+    }
+}
+
+// The `From` conversions panic on rows of different lengths, use
+// `try_from_rows` to get an error instead.
 impl From<Vec<Vector>> for Matrix {
     fn from(value: Vec<Vector>) -> Self {
-        Self {
-            dim: value.len(),
-            data: value,
-        }
+        Self::from(value.as_slice())
     }
 }
 
 impl From<&[Vector]> for Matrix {
     fn from(value: &[Vector]) -> Self {
-        Self {
-            dim: value.len(),
-            data: value.to_vec(),
-        }
+        Self::try_from_slices(value.iter().map(|r| r.data())).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
 impl From<Vec<Vec<f64>>> for Matrix {
     fn from(value: Vec<Vec<f64>>) -> Self {
-        Self {
-            dim: value.len(),
-            data: value.par_iter().map(|v| Vector::from(v.clone())).collect(),
-        }
+        Self::try_from_slices(value.iter().map(|r| r.as_slice()))
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl From<Matrix> for Vector {
+    fn from(value: Matrix) -> Self {
+        Vector::from(value.data)
     }
 }
 
 impl PartialEq for Matrix {
     fn eq(&self, other: &Self) -> bool {
-        self.dim == other.dim && self.data == other.data
+        self.rows == other.rows && self.cols == other.cols && self.data == other.data
     }
 }
 
 impl Eq for Matrix {}
 
+// Indexing with a single integer gives a row.
 impl Index<usize> for Matrix {
-    type Output = Vector;
+    type Output = [f64];
     fn index(&self, index: usize) -> &Self::Output {
-        &self.data[index]
+        self.row(index)
     }
 }
 
 impl IndexMut<usize> for Matrix {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.data[index]
+        self.row_mut(index)
+    }
+}
+
+// Range indexing gives the selected rows, flattened, as `rows` does. Rows
+// used to be stored as separate vectors and came back as `[Vector]`.
+impl Index<RangeFrom<usize>> for Matrix {
+    type Output = [f64];
+    fn index(&self, index: RangeFrom<usize>) -> &Self::Output {
+        self.rows(index)
+    }
+}
+
+impl Index<RangeTo<usize>> for Matrix {
+    type Output = [f64];
+    fn index(&self, index: RangeTo<usize>) -> &Self::Output {
+        self.rows(index)
     }
 }
 
 impl Index<(usize, usize)> for Matrix {
     type Output = f64;
+    #[inline]
     fn index(&self, index: (usize, usize)) -> &Self::Output {
-        &self.data[index.0][index.1]
+        &self.data[self.offset(index)]
     }
 }
 
 impl IndexMut<(usize, usize)> for Matrix {
+    #[inline]
     fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
-        &mut self.data[index.0][index.1]
+        let k = self.offset(index);
+        &mut self.data[k]
     }
 }
 
-impl Index<RangeFrom<usize>> for Matrix {
-    type Output = [Vector];
-
-    fn index(&self, index: RangeFrom<usize>) -> &Self::Output {
-        &self.data[index]
-    }
-}
-
-impl Index<RangeTo<usize>> for Matrix {
-    type Output = [Vector];
-
-    fn index(&self, index: RangeTo<usize>) -> &Self::Output {
-        &self.data[index]
+impl Matrix {
+    fn zip_with(mut self, rhs: &Self, f: impl Fn(&mut f64, f64)) -> Self {
+        self.check_shape(rhs).unwrap_or_else(|e| panic!("{}", e));
+        self.data
+            .iter_mut()
+            .zip(&rhs.data)
+            .for_each(|(a, b)| f(a, *b));
+        self
+    }
+
+    fn zip_rows(mut self, rhs: &Vector, f: impl Fn(&mut f64, f64)) -> Self {
+        self.check_cols(rhs).unwrap_or_else(|e| panic!("{}", e));
+        for i in 0..self.rows {
+            self.row_mut(i)
+                .iter_mut()
+                .zip(rhs.data())
+                .for_each(|(a, b)| f(a, *b));
+        }
+        self
     }
 }
 
+// The operators panic when the shapes do not match; `try_add`, `try_sub`,
+// `try_add_vector`, `try_sub_vector`, `matmul` and `matvec` return the error
+// instead.
 impl Add for Matrix {
     type Output = Self;
     fn add(self, rhs: Self) -> Self::Output {
-        assert!(self.dim_eq(&rhs));
-        Self {
-            dim: self.dim,
-            data: self
-                .data
-                .par_iter()
-                .zip(rhs.data.par_iter())
-                .map(|(a, b)| a.clone() + b.clone())
-                .collect(),
-        }
+        self.zip_with(&rhs, |a, b| *a += b)
     }
 }
 
 impl AddAssign for Matrix {
     fn add_assign(&mut self, rhs: Self) {
-        assert!(self.dim_eq(&rhs));
-        self.data
-            .par_iter_mut()
-            .zip(rhs.data.par_iter())
-            .for_each(|(a, b)| *a += b.clone());
+        *self = std::mem::take(self).zip_with(&rhs, |a, b| *a += b);
     }
 }
 
 impl Add<Vector> for Matrix {
     type Output = Self;
     fn add(self, rhs: Vector) -> Self::Output {
-        assert!(self.shape().1 == rhs.dim());
-        Self {
-            dim: self.dim,
-            data: self
-                .data
-                .par_iter()
-                .map(|a| a.clone() + rhs.clone())
-                .collect(),
-        }
+        self.zip_rows(&rhs, |a, b| *a += b)
     }
 }
 
 impl AddAssign<Vector> for Matrix {
     fn add_assign(&mut self, rhs: Vector) {
-        assert!(self.shape().1 == rhs.dim());
-        self.data.par_iter_mut().for_each(|a| *a += rhs.clone());
+        *self = std::mem::take(self).zip_rows(&rhs, |a, b| *a += b);
     }
 }
 
 impl Sub for Matrix {
     type Output = Self;
     fn sub(self, rhs: Self) -> Self::Output {
-        assert!(self.dim_eq(&rhs));
-        Self {
-            dim: self.dim,
-            data: self
-                .data
-                .par_iter()
-                .zip(rhs.data.par_iter())
-                .map(|(a, b)| a.clone() - b.clone())
-                .collect(),
-        }
+        self.zip_with(&rhs, |a, b| *a -= b)
     }
 }
 
 impl SubAssign for Matrix {
     fn sub_assign(&mut self, rhs: Self) {
-        assert!(self.dim_eq(&rhs));
-        self.data
-            .par_iter_mut()
-            .zip(rhs.data.par_iter())
-            .for_each(|(a, b)| *a -= b.clone());
+        *self = std::mem::take(self).zip_with(&rhs, |a, b| *a -= b);
     }
 }
 
 impl Sub<Vector> for Matrix {
     type Output = Self;
     fn sub(self, rhs: Vector) -> Self::Output {
-        assert!(self.shape().1 == rhs.dim());
-        Self {
-            dim: self.dim,
-            data: self
-                .data
-                .par_iter()
-                .map(|a| a.clone() - rhs.clone())
-                .collect(),
-        }
+        self.zip_rows(&rhs, |a, b| *a -= b)
     }
 }
 
 impl SubAssign<Vector> for Matrix {
     fn sub_assign(&mut self, rhs: Vector) {
-        assert!(self.shape().1 == rhs.dim());
-        self.data.par_iter_mut().for_each(|a| *a -= rhs.clone());
+        *self = std::mem::take(self).zip_rows(&rhs, |a, b| *a -= b);
+    }
+}
+
+impl Mul for Matrix {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.matmul(&rhs).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl Mul<Vector> for Matrix {
+    type Output = Vector;
+    fn mul(self, rhs: Vector) -> Self::Output {
+        self.matvec(&rhs).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
 impl Mul<f64> for Matrix {
     type Output = Self;
-    fn mul(self, rhs: f64) -> Self::Output {
-        Self {
-            dim: self.dim,
-            data: self.data.par_iter().map(|a| a.clone() * rhs).collect(),
-        }
+    fn mul(mut self, rhs: f64) -> Self::Output {
+        self *= rhs;
+        self
     }
 }
 
 impl MulAssign<f64> for Matrix {
     fn mul_assign(&mut self, rhs: f64) {
-        self.data.par_iter_mut().for_each(|a| *a *= rhs);
+        self.data.iter_mut().for_each(|a| *a *= rhs);
     }
 }
 
 impl Div<f64> for Matrix {
     type Output = Self;
-    fn div(self, rhs: f64) -> Self::Output {
-        Self {
-            dim: self.dim,
-            data: self.data.par_iter().map(|a| a.clone() / rhs).collect(),
-        }
+    fn div(mut self, rhs: f64) -> Self::Output {
+        self /= rhs;
+        self
     }
 }
 
 impl DivAssign<f64> for Matrix {
     fn div_assign(&mut self, rhs: f64) {
-        self.data.par_iter_mut().for_each(|a| *a /= rhs);
+        self.data.iter_mut().for_each(|a| *a /= rhs);
+    }
+}
+
+#[cfg(test)]
+mod matrix_tests {
+    use super::*;
+
+    #[test]
+    fn test_try_ops() {
+        let a = Matrix::from(vec![vec![1., 2.], vec![3., 4.]]);
+        let b = Matrix::from(vec![vec![1., 2., 3.], vec![3., 4., 5.]]);
+        assert_eq!(
+            a.try_add(&b),
+            Err(SimError::ShapeMismatch {
+                expected: (2, 2),
+                found: (2, 3)
+            })
+        );
+        assert_eq!(
+            a.try_add(&a),
+            Ok(Matrix::from(vec![vec![2., 4.], vec![6., 8.]]))
+        );
+        assert!(a.try_add_vector(&Vector::from(vec![1., 2., 3.])).is_err());
+        assert_eq!(
+            Matrix::from(Vec::<Vector>::new()).try_shape(),
+            Err(SimError::EmptyMatrix)
+        );
+        assert!(Matrix::try_from_rows(vec![Vector::new(2), Vector::new(3)]).is_err());
+    }
+
+    #[test]
+    fn test_views() {
+        let mut a = Matrix::from(vec![vec![1., 2., 3.], vec![4., 5., 6.]]);
+        assert_eq!(a.ravel(), &[1., 2., 3., 4., 5., 6.]);
+        assert_eq!(a[1], [4., 5., 6.]);
+        assert_eq!(a[1..], [4., 5., 6.]);
+        assert_eq!(a[..1], [1., 2., 3.]);
+        assert_eq!(a.rows(0..=1), a.ravel());
+        assert!(a.rows(1..1).is_empty());
+        let col = a.column(1);
+        assert_eq!((col.len(), col[0], col[1], col.get(2)), (2, 2., 5., None));
+        assert_eq!(
+            a.iter_columns().map(|c| c.to_vector()).collect::<Vec<_>>(),
+            vec![
+                Vector::from(vec![1., 4.]),
+                Vector::from(vec![2., 5.]),
+                Vector::from(vec![3., 6.])
+            ]
+        );
+        assert_eq!(a.sum(), Vector::from(vec![5., 7., 9.]));
+        a.swap_rows(0, 1);
+        a.row_mut(1)[2] = 0.0;
+        assert_eq!(a, Matrix::from(vec![vec![4., 5., 6.], vec![1., 2., 0.]]));
+        assert_eq!(a.last(), Some(Vector::from(vec![1., 2., 0.])));
+        assert_eq!(
+            Matrix::try_from_vec((2, 2), vec![1., 2., 3.]),
+            Err(SimError::DimensionMismatch {
+                expected: 4,
+                found: 3
+            })
+        );
+        let mut ones = Matrix::ones((2, 2));
+        ones += Vector::from(vec![1., -1.]);
+        assert_eq!(ones.ravel(), &[2., 0., 2., 0.]);
+    }
+
+    #[test]
+    fn test_products() {
+        let a = Matrix::from(vec![vec![1., 2., 3.], vec![4., 5., 6.]]);
+        let at = a.transpose();
+        assert_eq!(
+            at,
+            Matrix::from(vec![vec![1., 4.], vec![2., 5.], vec![3., 6.]])
+        );
+        assert_eq!(
+            a.clone() * at.clone(),
+            Matrix::from(vec![vec![14., 32.], vec![32., 77.]])
+        );
+        assert_eq!(
+            a.matmul(&a),
+            Err(SimError::DimensionMismatch {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            a.clone() * Vector::from(vec![1., 0., -1.]),
+            Vector::from(vec![-2., -2.])
+        );
+        assert_eq!(a.matmul(&Matrix::identity(3)), Ok(a.clone()));
+        assert_eq!(a.trace(), Err(SimError::NotSquare { rows: 2, cols: 3 }));
+        assert_eq!((at * a).trace(), Ok(91.0));
+    }
+
+    #[test]
+    fn test_inverse() {
+        // The zero leading entry needs a row swap.
+        let a = Matrix::from(vec![vec![0., 2., 1.], vec![1., 1., 0.], vec![2., 0., 3.]]);
+        assert!((a.det().unwrap() + 8.0).abs() < 1e-12);
+        let inv = a.inverse().unwrap();
+        let eye = a.matmul(&inv).unwrap();
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((eye[(i, j)] - expected).abs() < 1e-12);
+            }
+        }
+        let x = a.solve(&Vector::from(vec![3., 2., 5.])).unwrap();
+        assert!((0..3).all(|i| (x[i] - 1.0).abs() < 1e-12));
+
+        let singular = Matrix::from(vec![vec![1., 2.], vec![2., 4.]]);
+        assert_eq!(singular.det(), Ok(0.0));
+        assert_eq!(singular.inverse(), Err(SimError::SingularMatrix));
+        assert_eq!(
+            singular.solve(&Vector::from(vec![1., 2.])),
+            Err(SimError::SingularMatrix)
+        );
+        assert!(a.solve(&Vector::from(vec![1., 2.])).is_err());
     }
 }
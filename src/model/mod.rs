@@ -1,12 +1,27 @@
 pub(crate) mod value;
 pub use value::*;
 
+#[cfg(feature = "matrix")]
+pub(crate) mod decomposition;
+#[cfg(feature = "matrix")]
+pub use decomposition::{Cholesky, Eigenvalues, Lu, Qr, Svd, SymmetricEigen};
+// Shared by the continuous and discrete linear systems.
+#[cfg(all(feature = "matrix", any(feature = "continuous", feature = "discrete")))]
+pub(crate) mod linear;
 #[cfg(feature = "matrix")]
 pub(crate) mod matrix;
 #[cfg(feature = "matrix")]
-pub use matrix::Matrix;
+pub use matrix::{Column, Matrix};
+#[cfg(feature = "matrix")]
+pub(crate) mod smatrix;
+#[cfg(feature = "matrix")]
+pub use smatrix::SMatrix;
 
 #[cfg(feature = "vector")]
 pub(crate) mod vector;
 #[cfg(feature = "vector")]
 pub use vector::Vector;
+#[cfg(feature = "vector")]
+pub(crate) mod svector;
+#[cfg(feature = "vector")]
+pub use svector::SVector;
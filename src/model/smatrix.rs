@@ -0,0 +1,284 @@
+use super::{matrix::Matrix, svector::SVector, IsValue};
+use crate::utils::SimError;
+use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Sub, SubAssign};
+
+// Fixed-size row-major matrix stored inline, e.g. rotations and inertia
+// tensors. Shapes are checked at compile time, so the operators cannot fail.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SMatrix<const R: usize, const C: usize> {
+    data: [[f64; C]; R],
+}
+
+impl<const R: usize, const C: usize> SMatrix<R, C> {
+    pub fn new(data: [[f64; C]; R]) -> Self {
+        Self { data }
+    }
+
+    pub fn from_fn(f: impl Fn(usize, usize) -> f64) -> Self {
+        Self::new(std::array::from_fn(|i| std::array::from_fn(|j| f(i, j))))
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (R, C)
+    }
+
+    pub fn data(&self) -> &[[f64; C]; R] {
+        &self.data
+    }
+
+    // The elements row by row, without copying.
+    pub fn ravel(&self) -> &[f64] {
+        self.data.as_flattened()
+    }
+
+    pub fn zero() -> Self {
+        Self::new([[0.0; C]; R])
+    }
+
+    pub fn ones() -> Self {
+        Self::new([[1.0; C]; R])
+    }
+
+    pub fn fill(&mut self, value: f64) {
+        self.data = [[value; C]; R];
+    }
+
+    pub fn has_nan(&self) -> bool {
+        self.ravel().iter().any(|x| x.is_nan())
+    }
+
+    pub fn row(&self, i: usize) -> SVector<C> {
+        SVector::new(self.data[i])
+    }
+
+    pub fn column(&self, j: usize) -> SVector<R> {
+        SVector::new(std::array::from_fn(|i| self.data[i][j]))
+    }
+
+    pub fn transpose(&self) -> SMatrix<C, R> {
+        SMatrix::from_fn(|i, j| self.data[j][i])
+    }
+
+    fn map(&self, f: impl Fn(f64) -> f64) -> Self {
+        Self::from_fn(|i, j| f(self.data[i][j]))
+    }
+
+    fn zip_with(&self, other: &Self, f: impl Fn(f64, f64) -> f64) -> Self {
+        Self::from_fn(|i, j| f(self.data[i][j], other.data[i][j]))
+    }
+
+    fn zip_rows(&self, other: &SVector<C>, f: impl Fn(f64, f64) -> f64) -> Self {
+        Self::from_fn(|i, j| f(self.data[i][j], other[j]))
+    }
+}
+
+impl<const N: usize> SMatrix<N, N> {
+    pub fn identity() -> Self {
+        Self::from_fn(|i, j| if i == j { 1.0 } else { 0.0 })
+    }
+
+    pub fn trace(&self) -> f64 {
+        (0..N).map(|i| self.data[i][i]).sum()
+    }
+}
+
+impl<const R: usize, const C: usize> Default for SMatrix<R, C> {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl<const R: usize, const C: usize> IsValue for SMatrix<R, C> {
+    fn has_nan(&self) -> bool {
+        SMatrix::has_nan(self)
+    }
+}
+
+impl<const R: usize, const C: usize> From<[[f64; C]; R]> for SMatrix<R, C> {
+    fn from(value: [[f64; C]; R]) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<const R: usize, const C: usize> From<SMatrix<R, C>> for Matrix {
+    fn from(value: SMatrix<R, C>) -> Self {
+        Matrix::from_fn((R, C), |i, j| value.data[i][j])
+    }
+}
+
+impl<const R: usize, const C: usize> TryFrom<&Matrix> for SMatrix<R, C> {
+    type Error = SimError;
+    fn try_from(value: &Matrix) -> Result<Self, Self::Error> {
+        let found = value.try_shape()?;
+        if found != (R, C) {
+            return Err(SimError::ShapeMismatch {
+                expected: (R, C),
+                found,
+            });
+        }
+        Ok(Self::from_fn(|i, j| value[(i, j)]))
+    }
+}
+
+impl<const R: usize, const C: usize> TryFrom<Matrix> for SMatrix<R, C> {
+    type Error = SimError;
+    fn try_from(value: Matrix) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+// Indexing with a single integer gives a row.
+impl<const R: usize, const C: usize> Index<usize> for SMatrix<R, C> {
+    type Output = [f64; C];
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl<const R: usize, const C: usize> IndexMut<usize> for SMatrix<R, C> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.data[index]
+    }
+}
+
+impl<const R: usize, const C: usize> Index<(usize, usize)> for SMatrix<R, C> {
+    type Output = f64;
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        &self.data[index.0][index.1]
+    }
+}
+
+impl<const R: usize, const C: usize> IndexMut<(usize, usize)> for SMatrix<R, C> {
+    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
+        &mut self.data[index.0][index.1]
+    }
+}
+
+impl<const R: usize, const C: usize> Add for SMatrix<R, C> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip_with(&rhs, |a, b| a + b)
+    }
+}
+
+impl<const R: usize, const C: usize> AddAssign for SMatrix<R, C> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const R: usize, const C: usize> Add<SVector<C>> for SMatrix<R, C> {
+    type Output = Self;
+    fn add(self, rhs: SVector<C>) -> Self::Output {
+        self.zip_rows(&rhs, |a, b| a + b)
+    }
+}
+
+impl<const R: usize, const C: usize> AddAssign<SVector<C>> for SMatrix<R, C> {
+    fn add_assign(&mut self, rhs: SVector<C>) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const R: usize, const C: usize> Sub for SMatrix<R, C> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip_with(&rhs, |a, b| a - b)
+    }
+}
+
+impl<const R: usize, const C: usize> SubAssign for SMatrix<R, C> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const R: usize, const C: usize> Sub<SVector<C>> for SMatrix<R, C> {
+    type Output = Self;
+    fn sub(self, rhs: SVector<C>) -> Self::Output {
+        self.zip_rows(&rhs, |a, b| a - b)
+    }
+}
+
+impl<const R: usize, const C: usize> SubAssign<SVector<C>> for SMatrix<R, C> {
+    fn sub_assign(&mut self, rhs: SVector<C>) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const R: usize, const C: usize, const K: usize> Mul<SMatrix<C, K>> for SMatrix<R, C> {
+    type Output = SMatrix<R, K>;
+    fn mul(self, rhs: SMatrix<C, K>) -> Self::Output {
+        SMatrix::from_fn(|i, j| (0..C).map(|k| self.data[i][k] * rhs.data[k][j]).sum())
+    }
+}
+
+impl<const R: usize, const C: usize> Mul<SVector<C>> for SMatrix<R, C> {
+    type Output = SVector<R>;
+    fn mul(self, rhs: SVector<C>) -> Self::Output {
+        SVector::new(std::array::from_fn(|i| self.row(i).dot(&rhs)))
+    }
+}
+
+impl<const R: usize, const C: usize> Mul<f64> for SMatrix<R, C> {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self::Output {
+        self.map(|x| x * rhs)
+    }
+}
+
+impl<const R: usize, const C: usize> MulAssign<f64> for SMatrix<R, C> {
+    fn mul_assign(&mut self, rhs: f64) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const R: usize, const C: usize> Div<f64> for SMatrix<R, C> {
+    type Output = Self;
+    fn div(self, rhs: f64) -> Self::Output {
+        self.map(|x| x / rhs)
+    }
+}
+
+impl<const R: usize, const C: usize> DivAssign<f64> for SMatrix<R, C> {
+    fn div_assign(&mut self, rhs: f64) {
+        *self = *self / rhs;
+    }
+}
+
+#[cfg(test)]
+mod smatrix_tests {
+    use super::*;
+
+    #[test]
+    fn test_ops() {
+        let a = SMatrix::new([[1., 2., 3.], [4., 5., 6.]]);
+        let at = a.transpose();
+        assert_eq!(at, SMatrix::new([[1., 4.], [2., 5.], [3., 6.]]));
+        assert_eq!(a * at, SMatrix::new([[14., 32.], [32., 77.]]));
+        assert_eq!(a * SVector::new([1., 0., -1.]), SVector::new([-2., -2.]));
+        assert_eq!(a * SMatrix::identity(), a);
+        assert_eq!((at * a).trace(), 91.0);
+        assert_eq!(
+            (a + SVector::new([1., 1., 1.])) * 2.0 - a,
+            SMatrix::new([[3., 4., 5.], [6., 7., 8.]])
+        );
+        assert_eq!(a.column(1), SVector::new([2., 5.]));
+        assert_eq!(a.ravel(), &[1., 2., 3., 4., 5., 6.]);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let a = SMatrix::new([[1., 2.], [3., 4.]]);
+        let m = Matrix::from(a);
+        assert_eq!(m, Matrix::from(vec![vec![1., 2.], vec![3., 4.]]));
+        assert_eq!(SMatrix::<2, 2>::try_from(&m), Ok(a));
+        assert_eq!(
+            SMatrix::<2, 3>::try_from(m),
+            Err(SimError::ShapeMismatch {
+                expected: (2, 3),
+                found: (2, 2)
+            })
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use super::{
+    vector::{self, Vector},
+    IsValue,
+};
+use crate::utils::SimError;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Range, RangeFrom, RangeFull,
+    RangeTo, Sub, SubAssign,
+};
+
+// Fixed-size vector stored inline, for small states such as positions or
+// quaternions. Operations never allocate and the dimension is checked at
+// compile time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SVector<const N: usize> {
+    data: [f64; N],
+}
+
+impl<const N: usize> SVector<N> {
+    pub fn new(data: [f64; N]) -> Self {
+        Self { data }
+    }
+
+    pub fn dim(&self) -> usize {
+        N
+    }
+
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut [f64] {
+        &mut self.data
+    }
+
+    pub fn norm_sq(&self) -> f64 {
+        self.dot(self)
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.norm_sq().sqrt()
+    }
+
+    pub fn fill(&mut self, value: f64) {
+        self.data = [value; N];
+    }
+
+    pub fn normalize(&self) -> Self {
+        *self / self.norm()
+    }
+
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.data.iter().zip(&other.data).map(|(a, b)| a * b).sum()
+    }
+
+    pub fn has_nan(&self) -> bool {
+        self.data.iter().any(|x| x.is_nan())
+    }
+
+    pub fn zero() -> Self {
+        Self::new([0.0; N])
+    }
+
+    pub fn ones() -> Self {
+        Self::new([1.0; N])
+    }
+
+    pub fn abs(&self) -> Self {
+        self.map(|x| x.abs())
+    }
+
+    pub fn max(&self) -> f64 {
+        self.try_max().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_max(&self) -> Result<f64, SimError> {
+        vector::try_max(&self.data)
+    }
+
+    pub fn min(&self) -> f64 {
+        self.try_min().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_min(&self) -> Result<f64, SimError> {
+        vector::try_min(&self.data)
+    }
+
+    pub fn map(&self, f: impl Fn(&f64) -> f64) -> Self {
+        Self::new(std::array::from_fn(|i| f(&self.data[i])))
+    }
+
+    fn zip_with(&self, other: &Self, f: impl Fn(f64, f64) -> f64) -> Self {
+        Self::new(std::array::from_fn(|i| f(self.data[i], other.data[i])))
+    }
+}
+
+impl SVector<3> {
+    pub fn cross(&self, other: &Self) -> Self {
+        let (a, b) = (&self.data, &other.data);
+        Self::new([
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ])
+    }
+}
+
+impl<const N: usize> Default for SVector<N> {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl<const N: usize> IsValue for SVector<N> {
+    fn has_nan(&self) -> bool {
+        SVector::has_nan(self)
+    }
+}
+
+impl<const N: usize> From<[f64; N]> for SVector<N> {
+    fn from(value: [f64; N]) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<const N: usize> From<SVector<N>> for [f64; N] {
+    fn from(value: SVector<N>) -> Self {
+        value.data
+    }
+}
+
+impl<const N: usize> From<SVector<N>> for Vector {
+    fn from(value: SVector<N>) -> Self {
+        Vector::from(value.data())
+    }
+}
+
+impl<const N: usize> TryFrom<&[f64]> for SVector<N> {
+    type Error = SimError;
+    fn try_from(value: &[f64]) -> Result<Self, Self::Error> {
+        let data = value.try_into().map_err(|_| SimError::DimensionMismatch {
+            expected: N,
+            found: value.len(),
+        })?;
+        Ok(Self::new(data))
+    }
+}
+
+impl<const N: usize> TryFrom<&Vector> for SVector<N> {
+    type Error = SimError;
+    fn try_from(value: &Vector) -> Result<Self, Self::Error> {
+        Self::try_from(value.data())
+    }
+}
+
+impl<const N: usize> TryFrom<Vector> for SVector<N> {
+    type Error = SimError;
+    fn try_from(value: Vector) -> Result<Self, Self::Error> {
+        Self::try_from(value.data())
+    }
+}
+
+impl<const N: usize> Index<usize> for SVector<N> {
+    type Output = f64;
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for SVector<N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.data[index]
+    }
+}
+
+impl<const N: usize> Index<RangeFrom<usize>> for SVector<N> {
+    type Output = [f64];
+
+    fn index(&self, index: RangeFrom<usize>) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl<const N: usize> Index<RangeTo<usize>> for SVector<N> {
+    type Output = [f64];
+
+    fn index(&self, index: RangeTo<usize>) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl<const N: usize> Index<Range<usize>> for SVector<N> {
+    type Output = [f64];
+
+    fn index(&self, index: Range<usize>) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl<const N: usize> Index<RangeFull> for SVector<N> {
+    type Output = [f64];
+
+    fn index(&self, index: RangeFull) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl<const N: usize> Add for SVector<N> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip_with(&rhs, |a, b| a + b)
+    }
+}
+
+impl<const N: usize> AddAssign for SVector<N> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const N: usize> Sub for SVector<N> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip_with(&rhs, |a, b| a - b)
+    }
+}
+
+impl<const N: usize> SubAssign for SVector<N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const N: usize> Add<f64> for SVector<N> {
+    type Output = Self;
+    fn add(self, rhs: f64) -> Self::Output {
+        self.map(|x| x + rhs)
+    }
+}
+
+impl<const N: usize> AddAssign<f64> for SVector<N> {
+    fn add_assign(&mut self, rhs: f64) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const N: usize> Sub<f64> for SVector<N> {
+    type Output = Self;
+    fn sub(self, rhs: f64) -> Self::Output {
+        self.map(|x| x - rhs)
+    }
+}
+
+impl<const N: usize> SubAssign<f64> for SVector<N> {
+    fn sub_assign(&mut self, rhs: f64) {
+        *self = *self - rhs;
+    }
+}
+
+// Element-wise, as for `Vector`.
+impl<const N: usize> Mul for SVector<N> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.zip_with(&rhs, |a, b| a * b)
+    }
+}
+
+impl<const N: usize> MulAssign for SVector<N> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const N: usize> Div for SVector<N> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        self.zip_with(&rhs, |a, b| a / b)
+    }
+}
+
+impl<const N: usize> DivAssign for SVector<N> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<const N: usize> Mul<f64> for SVector<N> {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self::Output {
+        self.map(|x| x * rhs)
+    }
+}
+
+impl<const N: usize> MulAssign<f64> for SVector<N> {
+    fn mul_assign(&mut self, rhs: f64) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const N: usize> Div<f64> for SVector<N> {
+    type Output = Self;
+    fn div(self, rhs: f64) -> Self::Output {
+        self.map(|x| x / rhs)
+    }
+}
+
+impl<const N: usize> DivAssign<f64> for SVector<N> {
+    fn div_assign(&mut self, rhs: f64) {
+        *self = *self / rhs;
+    }
+}
+
+#[cfg(test)]
+mod svector_tests {
+    use super::*;
+
+    #[test]
+    fn test_ops() {
+        let a = SVector::new([1., 2., 2.]);
+        let b = SVector::from([0., 1., 0.]);
+        assert_eq!(a.norm(), 3.0);
+        assert_eq!(a.dot(&b), 2.0);
+        assert_eq!(a.cross(&b), SVector::new([-2., 0., 1.]));
+        assert_eq!(a + b * 2.0, SVector::new([1., 4., 2.]));
+        assert_eq!((a - 1.0) / 2.0, SVector::new([0., 0.5, 0.5]));
+        let mut c = a;
+        c *= b;
+        c += SVector::ones();
+        assert_eq!(c, SVector::new([1., 3., 1.]));
+        assert_eq!(a.normalize()[0], 1.0 / 3.0);
+        assert_eq!((a.max(), a.min()), (2.0, 1.0));
+        let nan = SVector::new([1., f64::NAN, 3.]);
+        let v = Vector::from(nan);
+        assert_eq!((nan.max(), nan.min()), (v.max(), v.min()));
+        assert_eq!(SVector::<0>::new([]).try_max(), Err(SimError::EmptyVector));
+        assert_eq!(SVector::<0>::new([]).try_min(), Err(SimError::EmptyVector));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let a = SVector::new([1., 2., 3.]);
+        let v = Vector::from(a);
+        assert_eq!(v, Vector::from(vec![1., 2., 3.]));
+        assert_eq!(SVector::<3>::try_from(&v), Ok(a));
+        assert_eq!(
+            SVector::<4>::try_from(v),
+            Err(SimError::DimensionMismatch {
+                expected: 4,
+                found: 3
+            })
+        );
+        assert!(SVector::new([1., f64::NAN]).has_nan());
+    }
+}
@@ -1,4 +1,8 @@
-pub trait IsValue: Clone {}
+pub trait IsValue: Clone {
+    fn has_nan(&self) -> bool {
+        false
+    }
+}
 
 impl IsValue for bool {}
 impl IsValue for String {}
@@ -17,5 +21,14 @@ impl IsValue for i32 {}
 impl IsValue for i64 {}
 impl IsValue for i128 {}
 
-impl IsValue for f32 {}
-impl IsValue for f64 {}
+impl IsValue for f32 {
+    fn has_nan(&self) -> bool {
+        self.is_nan()
+    }
+}
+
+impl IsValue for f64 {
+    fn has_nan(&self) -> bool {
+        self.is_nan()
+    }
+}
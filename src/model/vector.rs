@@ -1,4 +1,5 @@
 use super::{matrix::Matrix, IsValue};
+use crate::utils::SimError;
 use rayon::prelude::*;
 use std::{
     cmp::Ordering,
@@ -6,8 +7,63 @@ use std::{
         Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Range, RangeFrom,
         RangeFull, RangeTo, Sub, SubAssign,
     },
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
 };
 
+// Element counts from which the reductions and element-wise maps below
+// accumulate in independent lanes the compiler can vectorize, and from which
+// they are split across the rayon pool. Below `LANES` a plain loop is fastest.
+const LANES: usize = 8;
+const DEFAULT_PARALLEL_THRESHOLD: usize = 1 << 16;
+
+static PARALLEL_THRESHOLD: AtomicUsize = AtomicUsize::new(DEFAULT_PARALLEL_THRESHOLD);
+
+fn is_parallel(len: usize) -> bool {
+    len >= PARALLEL_THRESHOLD.load(AtomicOrdering::Relaxed)
+}
+
+// Sum of `f(a[i], b[i])`.
+fn sum_zip(a: &[f64], b: &[f64], f: impl Fn(f64, f64) -> f64 + Send + Sync) -> f64 {
+    if is_parallel(a.len()) {
+        a.par_iter().zip(b.par_iter()).map(|(x, y)| f(*x, *y)).sum()
+    } else if a.len() >= LANES {
+        let mut lanes = [0.0; LANES];
+        let (a_chunks, b_chunks) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+        let rest = a_chunks
+            .remainder()
+            .iter()
+            .zip(b_chunks.remainder())
+            .map(|(x, y)| f(*x, *y))
+            .sum::<f64>();
+        for (x, y) in a_chunks.zip(b_chunks) {
+            for k in 0..LANES {
+                lanes[k] += f(x[k], y[k]);
+            }
+        }
+        lanes.iter().sum::<f64>() + rest
+    } else {
+        a.iter().zip(b).map(|(x, y)| f(*x, *y)).sum()
+    }
+}
+
+// Plain loops over contiguous data already vectorize, so element-wise
+// operations only choose between sequential and parallel.
+fn for_each_mut(data: &mut [f64], f: impl Fn(&mut f64) + Send + Sync) {
+    if is_parallel(data.len()) {
+        data.par_iter_mut().for_each(f);
+    } else {
+        data.iter_mut().for_each(f);
+    }
+}
+
+fn map_collect(data: &[f64], f: impl Fn(&f64) -> f64 + Send + Sync) -> Vec<f64> {
+    if is_parallel(data.len()) {
+        data.par_iter().map(f).collect()
+    } else {
+        data.iter().map(f).collect()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Vector {
     dim: usize,
@@ -34,42 +90,109 @@ impl Vector {
         &self.data
     }
 
+    pub fn data_mut(&mut self) -> &mut [f64] {
+        &mut self.data
+    }
+
+    // Length from which `norm`, `dot`, `fill`, `map`, `abs` and `normalize`
+    // run on the rayon pool. Shared by all vectors.
+    pub fn parallel_threshold() -> usize {
+        PARALLEL_THRESHOLD.load(AtomicOrdering::Relaxed)
+    }
+
+    pub fn set_parallel_threshold(len: usize) {
+        PARALLEL_THRESHOLD.store(len, AtomicOrdering::Relaxed);
+    }
+
     pub fn norm_sq(&self) -> f64 {
-        self.data.par_iter().map(|x| x.powi(2)).sum::<f64>()
+        sum_zip(&self.data, &self.data, |a, b| a * b)
     }
 
     pub fn norm(&self) -> f64 {
-        self.data.par_iter().map(|x| x.powi(2)).sum::<f64>().sqrt()
+        self.norm_sq().sqrt()
     }
 
     pub fn fill(&mut self, value: f64) {
-        self.data.par_iter_mut().for_each(|x| *x = value);
+        for_each_mut(&mut self.data, |x| *x = value);
     }
 
     pub fn normalize(&self) -> Self {
         let norm = self.norm();
         let mut vec = self.clone();
-        vec.data.par_iter_mut().for_each(|x| *x /= norm);
+        for_each_mut(&mut vec.data, |x| *x /= norm);
         vec
     }
 
     pub fn dot(&self, other: &Self) -> f64 {
-        assert!(self.dim_eq(other));
-        self.data
-            .par_iter()
-            .zip(other.data.par_iter())
-            .map(|(a, b)| a * b)
-            .sum()
+        self.try_dot(other).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_dot(&self, other: &Self) -> Result<f64, SimError> {
+        self.check_dim(other)?;
+        Ok(sum_zip(&self.data, &other.data, |a, b| a * b))
     }
 
     pub fn cross(&self, other: &Self) -> Self {
-        assert!(self.dim_eq(other));
-        assert!(self.dim == 3);
+        self.try_cross(other).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_cross(&self, other: &Self) -> Result<Self, SimError> {
+        if self.dim != 3 {
+            return Err(SimError::DimensionMismatch {
+                expected: 3,
+                found: self.dim,
+            });
+        }
+        self.check_dim(other)?;
         let mut out = Self::zero(self.dim);
         out[0] = self[1] * other[2] - self[2] * other[1];
         out[1] = self[2] * other[0] - self[0] * other[2];
         out[2] = self[0] * other[1] - self[1] * other[0];
-        out
+        Ok(out)
+    }
+
+    pub fn try_add(&self, other: &Self) -> Result<Self, SimError> {
+        self.zip_with(other, |a, b| a + b)
+    }
+
+    pub fn try_sub(&self, other: &Self) -> Result<Self, SimError> {
+        self.zip_with(other, |a, b| a - b)
+    }
+
+    pub fn try_mul(&self, other: &Self) -> Result<Self, SimError> {
+        self.zip_with(other, |a, b| a * b)
+    }
+
+    pub fn try_div(&self, other: &Self) -> Result<Self, SimError> {
+        self.zip_with(other, |a, b| a / b)
+    }
+
+    pub fn has_nan(&self) -> bool {
+        self.data.iter().any(|x| x.is_nan())
+    }
+
+    pub(crate) fn check_dim(&self, other: &Self) -> Result<(), SimError> {
+        if self.dim_eq(other) {
+            Ok(())
+        } else {
+            Err(SimError::DimensionMismatch {
+                expected: self.dim,
+                found: other.dim,
+            })
+        }
+    }
+
+    fn zip_with(&self, other: &Self, f: impl Fn(f64, f64) -> f64) -> Result<Self, SimError> {
+        self.check_dim(other)?;
+        Ok(Self {
+            dim: self.dim,
+            data: self
+                .data
+                .iter()
+                .zip(other.data.iter())
+                .map(|(a, b)| f(*a, *b))
+                .collect(),
+        })
     }
 
     pub fn zero(dim: usize) -> Self {
@@ -98,8 +221,8 @@ impl Vector {
         let data = self.data.clone();
         let mut data = data
             .iter()
-            .zip(other.data().iter())
-            .map(|(a, b)| (*a, b.clone()))
+            .zip(other.iter_rows())
+            .map(|(a, b)| (*a, b))
             .collect::<Vec<_>>();
         data.par_sort_by(|a, b| {
             if a.0.is_nan() || b.0.is_nan() {
@@ -107,18 +230,9 @@ impl Vector {
             };
             a.0.partial_cmp(&b.0).unwrap()
         });
-        let mut vec_a = Vec::with_capacity(data.len());
-        let mut vec_b = Vec::with_capacity(data.len());
-
-        let len = data.len().clone();
-        for i in 0..len {
-            vec_a.push(data[i].0.clone())
-        }
-        for i in 0..len {
-            vec_b.push(data[i].1.clone())
-        }
+        let (vec_a, vec_b): (Vec<_>, Vec<_>) = data.into_iter().unzip();
         self.data = vec_a;
-        Matrix::from(vec_b)
+        Matrix::from(vec_b.into_iter().map(|r| r.to_vec()).collect::<Vec<_>>())
     }
 
     pub fn arg_sort(&self) -> Vec<usize> {
@@ -133,41 +247,60 @@ impl Vector {
     }
 
     pub fn abs(&self) -> Self {
-        Vector::from(self.data.par_iter().map(|x| x.abs()).collect::<Vec<_>>())
+        Vector::from(map_collect(&self.data, |x| x.abs()))
     }
 
     pub fn max(&self) -> f64 {
-        self.data
-            .iter()
-            .max_by(|a, b| {
-                if a.is_nan() || b.is_nan() {
-                    return Ordering::Equal;
-                }
-                a.partial_cmp(b).unwrap()
-            })
-            .unwrap()
-            .clone()
+        self.try_max().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_max(&self) -> Result<f64, SimError> {
+        try_max(&self.data)
     }
 
     pub fn min(&self) -> f64 {
-        self.data
-            .iter()
-            .min_by(|a, b| {
-                if a.is_nan() || b.is_nan() {
-                    return Ordering::Equal;
-                }
-                a.partial_cmp(b).unwrap()
-            })
-            .unwrap()
-            .clone()
+        self.try_min().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_min(&self) -> Result<f64, SimError> {
+        try_min(&self.data)
     }
 
     pub fn map(&self, f: impl Fn(&f64) -> f64 + Send + Sync) -> Self {
-        Vector::from(self.data.par_iter().map(f).collect::<Vec<_>>())
+        Vector::from(map_collect(&self.data, f))
     }
 }
 
-impl IsValue for Vector {}
+// Shared with `SVector`; NaN elements compare equal to everything.
+pub(super) fn try_max(data: &[f64]) -> Result<f64, SimError> {
+    data.iter()
+        .max_by(|a, b| {
+            if a.is_nan() || b.is_nan() {
+                return Ordering::Equal;
+            }
+            a.partial_cmp(b).unwrap()
+        })
+        .copied()
+        .ok_or(SimError::EmptyVector)
+}
+
+pub(super) fn try_min(data: &[f64]) -> Result<f64, SimError> {
+    data.iter()
+        .min_by(|a, b| {
+            if a.is_nan() || b.is_nan() {
+                return Ordering::Equal;
+            }
+            a.partial_cmp(b).unwrap()
+        })
+        .copied()
+        .ok_or(SimError::EmptyVector)
+}
+
+impl IsValue for Vector {
+    fn has_nan(&self) -> bool {
+        Vector::has_nan(self)
+    }
+}
 
 impl From<Vec<f64>> for Vector {
     fn from(value: Vec<f64>) -> Self {
@@ -240,25 +373,18 @@ impl Index<RangeFull> for Vector {
     }
 }
 
+// The operators panic when the dimensions do not match; the `try_*` methods
+// return the error instead.
 impl Add for Vector {
     type Output = Self;
     fn add(self, rhs: Self) -> Self::Output {
-        assert!(self.dim_eq(&rhs));
-        Self {
-            dim: self.dim,
-            data: self
-                .data
-                .iter()
-                .zip(rhs.data.iter())
-                .map(|(a, b)| a + b)
-                .collect(),
-        }
+        self.try_add(&rhs).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
 impl AddAssign for Vector {
     fn add_assign(&mut self, rhs: Self) {
-        assert!(self.dim_eq(&rhs));
+        self.check_dim(&rhs).unwrap_or_else(|e| panic!("{}", e));
         self.data
             .iter_mut()
             .zip(rhs.data.iter())
@@ -269,22 +395,13 @@ impl AddAssign for Vector {
 impl Sub for Vector {
     type Output = Self;
     fn sub(self, rhs: Self) -> Self::Output {
-        assert!(self.dim_eq(&rhs));
-        Self {
-            dim: self.dim,
-            data: self
-                .data
-                .iter()
-                .zip(rhs.data.iter())
-                .map(|(a, b)| a - b)
-                .collect(),
-        }
+        self.try_sub(&rhs).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
 impl SubAssign for Vector {
     fn sub_assign(&mut self, rhs: Self) {
-        assert!(self.dim_eq(&rhs));
+        self.check_dim(&rhs).unwrap_or_else(|e| panic!("{}", e));
         self.data
             .iter_mut()
             .zip(rhs.data.iter())
@@ -327,22 +444,13 @@ impl SubAssign<f64> for Vector {
 impl Mul for Vector {
     type Output = Self;
     fn mul(self, rhs: Self) -> Self::Output {
-        assert!(self.dim_eq(&rhs));
-        Self {
-            dim: self.dim,
-            data: self
-                .data
-                .par_iter()
-                .zip(rhs.data.par_iter())
-                .map(|(a, b)| a * b)
-                .collect(),
-        }
+        self.try_mul(&rhs).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
 impl MulAssign for Vector {
     fn mul_assign(&mut self, rhs: Self) {
-        assert!(self.dim_eq(&rhs));
+        self.check_dim(&rhs).unwrap_or_else(|e| panic!("{}", e));
         self.data
             .par_iter_mut()
             .zip(rhs.data.par_iter())
@@ -353,22 +461,13 @@ impl MulAssign for Vector {
 impl Div for Vector {
     type Output = Self;
     fn div(self, rhs: Self) -> Self::Output {
-        assert!(self.dim_eq(&rhs));
-        Self {
-            dim: self.dim,
-            data: self
-                .data
-                .par_iter()
-                .zip(rhs.data.par_iter())
-                .map(|(a, b)| a / b)
-                .collect(),
-        }
+        self.try_div(&rhs).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
 impl DivAssign for Vector {
     fn div_assign(&mut self, rhs: Self) {
-        assert!(self.dim_eq(&rhs));
+        self.check_dim(&rhs).unwrap_or_else(|e| panic!("{}", e));
         self.data
             .par_iter_mut()
             .zip(rhs.data.par_iter())
@@ -420,6 +519,45 @@ mod utils_model_tests {
         assert_eq!(result, Vector::from(vec![5., 7., 9.]));
     }
 
+    #[test]
+    fn test_try_ops() {
+        let v_a = Vector::from(vec![1., 2., 3.]);
+        let v_b = Vector::from(vec![4., 5.]);
+        assert_eq!(
+            v_a.try_add(&v_b),
+            Err(SimError::DimensionMismatch {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert!(v_a.try_dot(&v_b).is_err());
+        assert!(v_b.try_cross(&v_b).is_err());
+        assert_eq!(v_a.try_dot(&v_a), Ok(14.0));
+        assert_eq!(Vector::new(0).try_max(), Err(SimError::EmptyVector));
+        assert_eq!(v_a.try_min(), Ok(1.0));
+    }
+
+    #[test]
+    fn test_adaptive_paths() {
+        // Small integers keep every summation order exact, so the plain,
+        // lane-wise and parallel paths must agree.
+        for len in [3, LANES, 100, Vector::parallel_threshold() + 5] {
+            let data = (0..len).map(|i| (i % 7) as f64 - 3.0).collect::<Vec<_>>();
+            let v = Vector::from(data.clone());
+            let expected = data.iter().map(|x| x * x).sum::<f64>();
+            assert_eq!(v.norm_sq(), expected);
+            assert_eq!(
+                v.dot(&v.abs()),
+                data.iter().map(|x| x * x.abs()).sum::<f64>()
+            );
+            assert_eq!(v.map(|x| 2.0 * x).data()[len - 1], 2.0 * data[len - 1]);
+            assert!((v.normalize().norm() - 1.0).abs() < 1e-12);
+            let mut ones = v.clone();
+            ones.fill(1.0);
+            assert_eq!(ones, Vector::ones(len));
+        }
+    }
+
     #[test]
     fn test_zip_sort() {
         let mut v = Vector::from(vec![2., 1., 3.]);
@@ -0,0 +1,82 @@
+use crate::utils::SimError;
+
+pub(crate) fn crosses(before: f64, after: f64) -> bool {
+    (before < 0.0 && after >= 0.0) || (before > 0.0 && after <= 0.0)
+}
+
+fn first_crossing(ga: &[f64], gb: &[f64], a: f64, b: f64) -> Option<usize> {
+    ga.iter()
+        .zip(gb.iter())
+        .enumerate()
+        .filter(|(_, (ga, gb))| crosses(**ga, **gb))
+        .map(|(i, (ga, gb))| (i, a + (b - a) * ga / (ga - gb)))
+        .min_by(|x, y| x.1.total_cmp(&y.1))
+        .map(|(i, _)| i)
+}
+
+// Locates the earliest sign change of the zero crossing functions `g` inside
+// `[a, b]` with the Illinois variant of regula falsi, returning the final
+// bracket around the crossing. Errors from evaluating `g` are passed on.
+pub(crate) fn locate(
+    g: &mut dyn FnMut(f64) -> Result<Vec<f64>, SimError>,
+    (mut a, mut ga): (f64, Vec<f64>),
+    (mut b, mut gb): (f64, Vec<f64>),
+    tolerance: f64,
+) -> Result<(f64, f64), SimError> {
+    let mut current = None;
+    let (mut wa, mut wb) = (1.0, 1.0);
+    let mut retained = 0;
+    for _ in 0..100 {
+        if b - a <= tolerance {
+            break;
+        }
+        let i = match first_crossing(&ga, &gb, a, b) {
+            Some(i) => i,
+            None => break,
+        };
+        if current != Some(i) {
+            current = Some(i);
+            (wa, wb) = (1.0, 1.0);
+            retained = 0;
+        }
+
+        let (fa, fb) = (ga[i] * wa, gb[i] * wb);
+        let mut c = (a * fb - b * fa) / (fb - fa);
+        if !(c > a && c < b) {
+            c = 0.5 * (a + b);
+        }
+        let gc = g(c)?;
+        if ga.iter().zip(gc.iter()).any(|(ga, gc)| crosses(*ga, *gc)) {
+            (b, gb) = (c, gc);
+            wb = 1.0;
+            retained = if retained < 0 { retained - 1 } else { -1 };
+            if retained <= -2 {
+                wa *= 0.5;
+            }
+        } else {
+            (a, ga) = (c, gc);
+            wa = 1.0;
+            retained = if retained > 0 { retained + 1 } else { 1 };
+            if retained >= 2 {
+                wb *= 0.5;
+            }
+        }
+    }
+    Ok((a, b))
+}
+
+#[cfg(test)]
+mod event_tests {
+    use super::*;
+
+    #[test]
+    fn test_locate_earliest() {
+        let mut g = |t: f64| Ok(vec![t * t - 2.0, t - 1.9, 5.0]);
+        let a = (1.0, g(1.0).unwrap());
+        let b = (2.0, g(2.0).unwrap());
+        let (a, b) = locate(&mut g, a, b, 1e-12).unwrap();
+        assert!(b - a <= 1e-12);
+        assert!(g(a).unwrap()[0] < 0.0 && g(b).unwrap()[0] >= 0.0);
+        assert!((b - 2.0f64.sqrt()).abs() < 1e-11);
+    }
+}
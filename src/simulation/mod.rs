@@ -0,0 +1,5 @@
+pub(crate) mod event;
+#[allow(clippy::module_inception)]
+pub(crate) mod simulation;
+
+pub use simulation::{Recording, Simulation};
@@ -0,0 +1,672 @@
+use super::event::{crosses, locate};
+use crate::{
+    diagram::{BlockId, Diagram},
+    model::IsValue,
+    solver::{AdaptiveSolver, Heun, Solver},
+    utils::SimError,
+};
+
+#[derive(Debug, Clone)]
+pub struct Recording<V: IsValue> {
+    times: Vec<f64>,
+    signals: Vec<Vec<V>>,
+}
+
+impl<V: IsValue> Recording<V> {
+    fn new(probes: usize) -> Self {
+        Self {
+            times: Vec::new(),
+            signals: vec![Vec::new(); probes],
+        }
+    }
+
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    pub fn signal(&self, probe: usize) -> Option<&[V]> {
+        self.signals.get(probe).map(|s| s.as_slice())
+    }
+
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    pub fn last(&self, probe: usize) -> Option<&V> {
+        self.signals.get(probe)?.last()
+    }
+}
+
+enum Stepper {
+    Fixed(Box<dyn Solver>),
+    Variable(Box<dyn AdaptiveSolver>),
+}
+
+pub struct Simulation<V: IsValue> {
+    diagram: Diagram<V>,
+    t_start: f64,
+    t_end: f64,
+    dt: f64,
+    stepper: Stepper,
+    probes: Vec<(BlockId, usize)>,
+}
+
+impl<V: IsValue> Simulation<V> {
+    pub fn new(diagram: Diagram<V>, t_start: f64, t_end: f64, dt: f64) -> Self {
+        Self {
+            diagram,
+            t_start,
+            t_end,
+            dt,
+            stepper: Stepper::Fixed(Box::new(Heun)),
+            probes: Vec::new(),
+        }
+    }
+
+    pub fn set_solver(&mut self, solver: impl Solver + 'static) {
+        self.stepper = Stepper::Fixed(Box::new(solver));
+    }
+
+    // `dt` becomes the initial step size when a variable step solver is used.
+    pub fn set_adaptive_solver(&mut self, solver: impl AdaptiveSolver + 'static) {
+        self.stepper = Stepper::Variable(Box::new(solver));
+    }
+
+    pub fn is_adaptive(&self) -> bool {
+        matches!(self.stepper, Stepper::Variable(_))
+    }
+
+    pub fn t_start(&self) -> f64 {
+        self.t_start
+    }
+
+    pub fn t_end(&self) -> f64 {
+        self.t_end
+    }
+
+    pub fn dt(&self) -> f64 {
+        self.dt
+    }
+
+    pub fn diagram(&self) -> &Diagram<V> {
+        &self.diagram
+    }
+
+    pub fn diagram_mut(&mut self) -> &mut Diagram<V> {
+        &mut self.diagram
+    }
+
+    pub fn into_diagram(self) -> Diagram<V> {
+        self.diagram
+    }
+
+    pub fn probe(&mut self, block: BlockId, port: usize) -> Result<usize, SimError> {
+        let name = self
+            .diagram
+            .name(block)
+            .ok_or(SimError::UnknownBlock(block.index()))?;
+        let ports = self.diagram.nodes[block.index()].block.num_outputs();
+        if port >= ports {
+            return Err(SimError::PortOutOfRange {
+                block: name.to_string(),
+                port,
+                ports,
+            });
+        }
+        self.probes.push((block, port));
+        Ok(self.probes.len() - 1)
+    }
+
+    pub fn run(&mut self) -> Result<Recording<V>, SimError> {
+        if self.dt <= 0.0 || self.dt.is_nan() {
+            return Err(SimError::InvalidParameter(format!(
+                "step size must be positive, got {}",
+                self.dt
+            )));
+        }
+        if self.t_end < self.t_start || self.t_end.is_nan() || self.t_start.is_nan() {
+            return Err(SimError::InvalidParameter(format!(
+                "end time {} is before start time {}",
+                self.t_end, self.t_start
+            )));
+        }
+        self.diagram.compile()?;
+        self.diagram.reset();
+
+        let mut recording = Recording::new(self.probes.len());
+        let mut g = Self::record(
+            &mut self.diagram,
+            &self.probes,
+            self.t_start,
+            &mut recording,
+        )?;
+        let mut x = self.diagram.states();
+        let mut t = self.t_start;
+        match &self.stepper {
+            Stepper::Fixed(solver) => {
+                let steps = ((self.t_end - self.t_start) / self.dt - 1e-9)
+                    .ceil()
+                    .max(0.0) as usize;
+                let mut k = 1;
+                while k <= steps {
+                    let grid = (self.t_start + k as f64 * self.dt).min(self.t_end);
+                    let next = match self.diagram.next_hit(t) {
+                        Some(hit) if hit < grid - 1e-10 * grid.abs().max(self.dt) => hit,
+                        _ => grid,
+                    };
+                    let y = Self::advance(&mut self.diagram, solver.as_ref(), t, &x, next - t)?;
+                    let (t_new, y) = Self::detect_event(
+                        &mut self.diagram,
+                        solver.as_ref(),
+                        (t, &x, &g),
+                        (next, y),
+                    )?;
+                    if t_new == grid {
+                        k += 1;
+                    }
+                    (t, x) = (t_new, y);
+                    g = Self::record(&mut self.diagram, &self.probes, t, &mut recording)?;
+                }
+            }
+            Stepper::Variable(solver) => {
+                let (h_min, h_max) = (solver.min_step(), solver.max_step());
+                let mut h = self.dt.clamp(h_min, h_max);
+                while t < self.t_end {
+                    h = h.min(self.t_end - t);
+                    if let Some(hit) = self.diagram.next_hit(t) {
+                        h = h.min(hit - t);
+                    }
+                    let (y, error) = loop {
+                        let (y, error) =
+                            solver.attempt(&mut Self::model(&mut self.diagram), t, &x, h);
+                        if error <= 1.0 {
+                            break (y, error);
+                        }
+                        if h <= h_min {
+                            return Err(SimError::SolverFailure(format!(
+                                "error tolerance not met at t = {} with minimum step size {}",
+                                t, h_min
+                            )));
+                        }
+                        h = solver.next_step(h, error).min(h);
+                    };
+                    let next = if self.t_end - (t + h) <= 1e-12 * self.t_end.abs().max(1.0) {
+                        self.t_end
+                    } else {
+                        t + h
+                    };
+                    let (t_new, y) = Self::detect_event(
+                        &mut self.diagram,
+                        solver.as_ref(),
+                        (t, &x, &g),
+                        (next, y),
+                    )?;
+                    (t, x) = (t_new, y);
+                    g = Self::record(&mut self.diagram, &self.probes, t, &mut recording)?;
+                    h = solver.next_step(h, error);
+                }
+            }
+        }
+        Ok(recording)
+    }
+
+    fn advance(
+        diagram: &mut Diagram<V>,
+        solver: &dyn Solver,
+        t: f64,
+        x: &[f64],
+        h: f64,
+    ) -> Result<Vec<f64>, SimError> {
+        if x.is_empty() {
+            return Ok(Vec::new());
+        }
+        solver.step(&mut Self::model(diagram), t, x, h)
+    }
+
+    // Checks the zero crossing functions over the step from `t` to `next` and,
+    // if any changed sign, shortens the step so that it ends just before the
+    // first crossing. The following step then starts on the event and only
+    // crosses it by the location tolerance, so no solver stage evaluates the
+    // discontinuity from the wrong side.
+    fn detect_event(
+        diagram: &mut Diagram<V>,
+        solver: &dyn Solver,
+        (t, x, g): (f64, &[f64], &[f64]),
+        (next, y): (f64, Vec<f64>),
+    ) -> Result<(f64, Vec<f64>), SimError> {
+        diagram.load_states(&y);
+        diagram.evaluate_outputs(next, false);
+        let g_next = diagram.zero_crossings(next);
+        if !g.iter().zip(g_next.iter()).any(|(a, b)| crosses(*a, *b)) {
+            return Ok((next, y));
+        }
+
+        let mut g_at = |tau: f64| {
+            let y = Self::advance(diagram, solver, t, x, tau - t)?;
+            diagram.load_states(&y);
+            diagram.evaluate_outputs(tau, false);
+            Ok(diagram.zero_crossings(tau))
+        };
+        let tolerance = 1e-12 * next.abs().max(1.0);
+        let (before, after) = locate(&mut g_at, (t, g.to_vec()), (next, g_next), tolerance)?;
+        let tau = if before - t <= tolerance {
+            after
+        } else {
+            before
+        };
+        let y = Self::advance(diagram, solver, t, x, tau - t)?;
+        diagram.load_states(&y);
+        Ok((tau, y))
+    }
+
+    fn model(diagram: &mut Diagram<V>) -> impl FnMut(f64, &[f64]) -> Vec<f64> + '_ {
+        |t: f64, x: &[f64]| {
+            diagram.load_states(x);
+            diagram.evaluate_outputs(t, false);
+            diagram.derivatives(t, x)
+        }
+    }
+
+    // Only accepted steps reach `update`, so sinks never see rejected or
+    // intermediate solver stages.
+    fn record(
+        diagram: &mut Diagram<V>,
+        probes: &[(BlockId, usize)],
+        t: f64,
+        recording: &mut Recording<V>,
+    ) -> Result<Vec<f64>, SimError> {
+        diagram.evaluate_outputs(t, true);
+        if let Some(block) = diagram.find_nan() {
+            return Err(SimError::NanEncountered {
+                t,
+                block: block.to_string(),
+            });
+        }
+        let g = diagram.zero_crossings(t);
+        diagram.update(t);
+        recording.times.push(t);
+        for (signal, (block, port)) in recording.signals.iter_mut().zip(probes.iter()) {
+            signal.push(diagram.nodes[block.index()].outputs[*port].clone());
+        }
+        Ok(g)
+    }
+}
+
+#[cfg(test)]
+mod simulation_tests {
+    use super::*;
+    use crate::{
+        block::{Block, ContinuousState, Source, Transfer},
+        diagram::SourceMutBlock,
+        solver::Rk4,
+        utils::fixtures::{Gain, Ramp},
+    };
+
+    struct Offset(f64, f64);
+
+    impl Transfer<f64, f64> for Offset {
+        fn transfer(&self, _t: f64, input: &f64) -> f64 {
+            self.1 * (input + self.0)
+        }
+    }
+
+    #[test]
+    fn test_fixed_step_grid() {
+        let mut d = Diagram::new();
+        let ramp = d.add_source("ramp", Ramp);
+        let gain = d.add_transfer("gain", Gain(2.0));
+        d.connect(ramp, 0, gain, 0).unwrap();
+
+        let mut sim = Simulation::new(d, 1.0, 2.0, 0.3);
+        let probe = sim.probe(gain, 0).unwrap();
+        let r = sim.run().unwrap();
+        assert_eq!(r.len(), 5);
+        assert_eq!(r.times()[0], 1.0);
+        assert_eq!(r.times()[4], 2.0);
+        assert_eq!(r.last(probe), Some(&4.0));
+    }
+
+    #[cfg(feature = "continuous")]
+    #[test]
+    fn test_integrate_ramp() {
+        use crate::{continuous::Integrator, solver::Euler};
+
+        let mut d = Diagram::new();
+        let ramp = d.add_source("ramp", Ramp);
+        let integrator = d.add("integrator", Integrator::new(0.0, false));
+        d.connect(ramp, 0, integrator, 0).unwrap();
+
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.01);
+        let probe = sim.probe(integrator, 0).unwrap();
+        let r = sim.run().unwrap();
+        assert!((r.last(probe).unwrap() - 0.5).abs() < 1e-12);
+
+        sim.set_solver(Euler);
+        let r = sim.run().unwrap();
+        assert!((r.last(probe).unwrap() - 0.495).abs() < 1e-9);
+    }
+
+    #[cfg(feature = "continuous")]
+    #[test]
+    fn test_feedback_solvers() {
+        use crate::continuous::Integrator;
+
+        let mut d = Diagram::new();
+        let integrator = d.add("integrator", Integrator::new(1.0, false));
+        let gain = d.add_transfer("gain", Gain(-1.0));
+        d.connect(integrator, 0, gain, 0).unwrap();
+        d.connect(gain, 0, integrator, 0).unwrap();
+
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.01);
+        sim.set_solver(Rk4);
+        let probe = sim.probe(integrator, 0).unwrap();
+        let r = sim.run().unwrap();
+        assert!((r.last(probe).unwrap() - (-1.0f64).exp()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_stateful_source() {
+        use crate::block::SourceMut;
+
+        #[derive(Clone)]
+        struct Steps(Vec<f64>);
+
+        impl SourceMut<f64> for Steps {
+            fn output_mut(&mut self, t: f64) -> f64 {
+                self.0.push(t);
+                self.0.len() as f64
+            }
+        }
+
+        let mut d = Diagram::new();
+        let steps = d.add_source_mut("steps", Steps(Vec::new()));
+        let gain = d.add_transfer("gain", Gain(2.0));
+        d.connect(steps, 0, gain, 0).unwrap();
+
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.25);
+        sim.set_solver(Rk4);
+        let probe = sim.probe(gain, 0).unwrap();
+        let r = sim.run().unwrap();
+        assert_eq!(r.last(probe), Some(&10.0));
+        let calls = &sim
+            .diagram()
+            .get::<SourceMutBlock<Steps>>(steps)
+            .unwrap()
+            .inner()
+            .0;
+        assert_eq!(calls, r.times());
+
+        // A second run starts again from the initial source.
+        let r = sim.run().unwrap();
+        assert_eq!(r.last(probe), Some(&10.0));
+    }
+
+    #[cfg(feature = "continuous")]
+    #[test]
+    fn test_variable_step() {
+        use crate::{block::Sink, continuous::Integrator, solver::DormandPrince};
+        use std::{cell::RefCell, rc::Rc};
+
+        struct Times(Rc<RefCell<Vec<f64>>>);
+
+        impl Sink<f64> for Times {
+            fn input(&self, t: f64, _value: &f64) {
+                self.0.borrow_mut().push(t);
+            }
+        }
+
+        // x' = -50 (x - 1): a fast transient followed by a flat response.
+        let mut d = Diagram::new();
+        let integrator = d.add("integrator", Integrator::new(0.0, false));
+        let gain = d.add_transfer("gain", Offset(-1.0, -50.0));
+        let times = Rc::new(RefCell::new(Vec::new()));
+        let sink = d.add_sink("sink", Times(times.clone()));
+        d.connect(integrator, 0, gain, 0).unwrap();
+        d.connect(gain, 0, integrator, 0).unwrap();
+        d.connect(integrator, 0, sink, 0).unwrap();
+
+        let mut sim = Simulation::new(d, 0.0, 2.0, 1e-4);
+        sim.set_adaptive_solver(DormandPrince::new(1e-8, 1e-10, 1e-12, 0.5).unwrap());
+        let probe = sim.probe(integrator, 0).unwrap();
+        let r = sim.run().unwrap();
+
+        assert_eq!(*r.times().last().unwrap(), 2.0);
+        assert!((r.last(probe).unwrap() - (1.0 - (-100.0f64).exp())).abs() < 1e-8);
+        assert!(r.len() < 400);
+        let steps = r
+            .times()
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .collect::<Vec<_>>();
+        assert!(steps[steps.len() - 2] > 10.0 * steps[1]);
+        assert_eq!(*times.borrow(), r.times());
+    }
+
+    #[cfg(feature = "continuous")]
+    #[test]
+    fn test_variable_step_nan() {
+        use crate::{solver::DormandPrince, utils::fixtures::Lag};
+
+        struct Blowup;
+
+        impl Source<f64> for Blowup {
+            fn output(&self, t: f64) -> f64 {
+                if t > 0.3 {
+                    f64::NAN
+                } else {
+                    1.0
+                }
+            }
+        }
+
+        // Every step reaching past t = 0.3 has a NaN error estimate, so the
+        // step shrinks down to the minimum and the run fails there.
+        let mut d = Diagram::new();
+        let source = d.add_source("source", Blowup);
+        let lag = d.add("lag", Lag(0.0));
+        d.connect(source, 0, lag, 0).unwrap();
+
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.1);
+        sim.set_adaptive_solver(DormandPrince::new(1e-6, 1e-9, 1e-6, 0.5).unwrap());
+        assert!(matches!(sim.run(), Err(SimError::SolverFailure(_))));
+    }
+
+    struct Oscillator {
+        x: Vec<f64>,
+    }
+
+    impl Block<f64> for Oscillator {
+        fn num_inputs(&self) -> usize {
+            0
+        }
+
+        fn num_outputs(&self) -> usize {
+            1
+        }
+
+        fn output(&mut self, _t: f64, _inputs: &[f64]) -> Vec<f64> {
+            vec![self.x[0]]
+        }
+
+        fn direct_feedthrough(&self) -> bool {
+            false
+        }
+
+        fn continuous_state(&self) -> Option<&dyn ContinuousState<f64>> {
+            Some(self)
+        }
+
+        fn continuous_state_mut(&mut self) -> Option<&mut dyn ContinuousState<f64>> {
+            Some(self)
+        }
+    }
+
+    impl ContinuousState<f64> for Oscillator {
+        fn state(&self) -> Vec<f64> {
+            self.x.clone()
+        }
+
+        fn set_state(&mut self, state: &[f64]) {
+            self.x = state.to_vec();
+        }
+
+        fn derivative(&self, _t: f64, x: &[f64], _u: &[f64]) -> Vec<f64> {
+            vec![x[1], -x[0]]
+        }
+    }
+
+    #[test]
+    fn test_global_state() {
+        let mut d = Diagram::new();
+        let osc = d.add("oscillator", Oscillator { x: vec![1.0, 0.0] });
+        let period = 2.0 * std::f64::consts::PI;
+        let mut sim = Simulation::new(d, 0.0, period, period / 200.0);
+        sim.set_solver(Rk4);
+        let probe = sim.probe(osc, 0).unwrap();
+        let r = sim.run().unwrap();
+        let quarter = r.signal(probe).unwrap()[50];
+        assert!(quarter.abs() < 1e-7);
+        let x = sim.diagram().states();
+        assert!((x[0] - 1.0).abs() < 1e-7);
+        assert!(x[1].abs() < 1e-7);
+    }
+
+    #[cfg(all(feature = "source", feature = "continuous"))]
+    #[test]
+    fn test_zero_crossing() {
+        use crate::{continuous::Integrator, solver::DormandPrince, source::Step};
+
+        let build = || {
+            let mut d = Diagram::new();
+            let step = d.add("step", Step::new(0.0, 1.0, 0.37));
+            let integrator = d.add("integrator", Integrator::new(0.0, false));
+            d.connect(step, 0, integrator, 0).unwrap();
+            (d, integrator)
+        };
+
+        let (d, integrator) = build();
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.1);
+        sim.set_adaptive_solver(DormandPrince::new(1e-3, 1e-3, 1e-9, 0.5).unwrap());
+        let probe = sim.probe(integrator, 0).unwrap();
+        let r = sim.run().unwrap();
+        assert!(r.times().iter().any(|t| (t - 0.37).abs() < 1e-10));
+        assert!((r.last(probe).unwrap() - 0.63).abs() < 1e-10);
+
+        let (d, integrator) = build();
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.1);
+        sim.set_solver(Rk4);
+        let probe = sim.probe(integrator, 0).unwrap();
+        let r = sim.run().unwrap();
+        assert_eq!(r.len(), 13);
+        assert!((r.last(probe).unwrap() - 0.63).abs() < 1e-10);
+
+        // The event survives adding the step through the source adapter.
+        let mut d = Diagram::new();
+        let step = d.add_source("step", Step::new(0.0, 1.0, 0.37));
+        let integrator = d.add("integrator", Integrator::new(0.0, false));
+        d.connect(step, 0, integrator, 0).unwrap();
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.1);
+        sim.set_solver(Rk4);
+        let probe = sim.probe(integrator, 0).unwrap();
+        let r = sim.run().unwrap();
+        assert_eq!(r.len(), 13);
+        assert!((r.last(probe).unwrap() - 0.63).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let mut sim = Simulation::new(Diagram::<f64>::new(), 0.0, 1.0, 0.0);
+        assert!(matches!(sim.run(), Err(SimError::InvalidParameter(_))));
+        let mut sim = Simulation::new(Diagram::<f64>::new(), 1.0, 0.0, 0.1);
+        assert!(matches!(sim.run(), Err(SimError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_nan_encountered() {
+        struct Sqrt;
+
+        impl Transfer<f64, f64> for Sqrt {
+            fn transfer(&self, _t: f64, input: &f64) -> f64 {
+                input.sqrt()
+            }
+        }
+
+        let mut d = Diagram::new();
+        let ramp = d.add_source("ramp", Ramp);
+        let offset = d.add_transfer("offset", Offset(-0.45, -1.0));
+        let sqrt = d.add_transfer("sqrt", Sqrt);
+        d.connect(ramp, 0, offset, 0).unwrap();
+        d.connect(offset, 0, sqrt, 0).unwrap();
+
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.1);
+        match sim.run() {
+            Err(SimError::NanEncountered { t, block }) => {
+                assert!((t - 0.5).abs() < 1e-12);
+                assert_eq!(block, "sqrt");
+            }
+            other => panic!("expected NaN error, got {:?}", other.map(|r| r.len())),
+        }
+    }
+
+    #[test]
+    fn test_triggered() {
+        use crate::block::{SampleTime, TriggerEdge};
+
+        struct Sine;
+
+        impl Source<f64> for Sine {
+            fn output(&self, t: f64) -> f64 {
+                (2.0 * std::f64::consts::PI * t).sin()
+            }
+        }
+
+        // Counts rising edges of its input and records when they happened.
+        struct Counter(Vec<f64>);
+
+        impl Block<f64> for Counter {
+            fn num_inputs(&self) -> usize {
+                1
+            }
+
+            fn num_outputs(&self) -> usize {
+                1
+            }
+
+            fn output(&mut self, _t: f64, _inputs: &[f64]) -> Vec<f64> {
+                vec![self.0.len() as f64]
+            }
+
+            fn direct_feedthrough(&self) -> bool {
+                false
+            }
+
+            fn update(&mut self, t: f64, _inputs: &[f64]) {
+                self.0.push(t);
+            }
+
+            fn zero_crossings(&self, _t: f64, inputs: &[f64]) -> Vec<f64> {
+                vec![inputs[0]]
+            }
+
+            fn sample_time(&self) -> SampleTime {
+                SampleTime::Triggered(TriggerEdge::Rising)
+            }
+        }
+
+        let mut d = Diagram::new();
+        let sine = d.add_source("sine", Sine);
+        let counter = d.add("counter", Counter(Vec::new()));
+        d.connect(sine, 0, counter, 0).unwrap();
+        let mut sim = Simulation::new(d, 0.1, 2.6, 0.3);
+        let probe = sim.probe(counter, 0).unwrap();
+        let r = sim.run().unwrap();
+        assert_eq!(r.last(probe), Some(&2.0));
+        let hits = &sim.diagram().get::<Counter>(counter).unwrap().0;
+        assert_eq!(hits.len(), 2);
+        assert!((hits[0] - 1.0).abs() < 1e-9 && (hits[1] - 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,301 @@
+use crate::{block::SinkMut, utils::SimError};
+use std::io::Write;
+
+#[cfg(feature = "matrix")]
+use crate::model::Matrix;
+#[cfg(feature = "vector")]
+use crate::model::Vector;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    JsonLines,
+}
+
+// Writes one (t, value...) row per sample. `SinkMut` cannot report failures,
+// so the first write error is kept and returned by `flush` and `finish`;
+// later samples are dropped once an error has occurred. Once the exporter is
+// owned by a diagram, reach it through `Diagram::get_mut` on its `SinkBlock`
+// to flush it; dropping the exporter flushes as well. Rows already written
+// stay in the writer, so a reset starts a new table, with its own CSV header,
+// after them.
+#[derive(Debug)]
+pub struct Exporter<W: Write> {
+    // Only taken by `finish`.
+    writer: Option<W>,
+    format: Format,
+    header: Option<Vec<String>>,
+    started: bool,
+    error: Option<SimError>,
+}
+
+impl<W: Write> Exporter<W> {
+    pub fn new(writer: W, format: Format) -> Self {
+        Self {
+            writer: Some(writer),
+            format,
+            header: None,
+            started: false,
+            error: None,
+        }
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    // Column names after `t`, used for the CSV header and the JSON keys.
+    // Without names the CSV header is `t,y` or `t,y0,y1,...` and JSON lines
+    // store the sample under `value`.
+    pub fn set_header(&mut self, names: Vec<String>) {
+        self.header = Some(names);
+    }
+
+    pub fn error(&self) -> Option<&SimError> {
+        self.error.as_ref()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        self.writer.as_ref().expect("writer taken by finish")
+    }
+
+    pub fn flush(&mut self) -> Result<(), SimError> {
+        if let Some(e) = &self.error {
+            return Err(e.clone());
+        }
+        self.writer_mut().flush()?;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W, SimError> {
+        self.flush()?;
+        Ok(self.writer.take().expect("writer taken by finish"))
+    }
+
+    fn restart(&mut self) {
+        self.started = false;
+    }
+
+    fn writer_mut(&mut self) -> &mut W {
+        self.writer.as_mut().expect("writer taken by finish")
+    }
+
+    fn write_row(&mut self, t: f64, values: &[f64], scalar: bool) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(e) = self.try_write_row(t, values, scalar) {
+            self.error = Some(e);
+        }
+    }
+
+    fn try_write_row(&mut self, t: f64, values: &[f64], scalar: bool) -> Result<(), SimError> {
+        if let Some(names) = &self.header {
+            if names.len() != values.len() {
+                return Err(SimError::DimensionMismatch {
+                    expected: names.len(),
+                    found: values.len(),
+                });
+            }
+        }
+        let line = match self.format {
+            Format::Csv => {
+                let mut line = String::new();
+                if !self.started {
+                    line.push_str(&self.csv_header(values.len(), scalar));
+                    line.push('\n');
+                }
+                line.push_str(&t.to_string());
+                for v in values {
+                    line.push(',');
+                    line.push_str(&v.to_string());
+                }
+                line
+            }
+            Format::JsonLines => {
+                let mut line = format!("{{\"t\":{}", json_number(t));
+                match &self.header {
+                    Some(names) => {
+                        for (name, v) in names.iter().zip(values.iter()) {
+                            line.push_str(&format!(
+                                ",\"{}\":{}",
+                                json_escape(name),
+                                json_number(*v)
+                            ));
+                        }
+                    }
+                    None if scalar => {
+                        line.push_str(&format!(",\"value\":{}", json_number(values[0])));
+                    }
+                    None => {
+                        let items = values.iter().map(|v| json_number(*v)).collect::<Vec<_>>();
+                        line.push_str(&format!(",\"value\":[{}]", items.join(",")));
+                    }
+                }
+                line.push('}');
+                line
+            }
+        };
+        self.started = true;
+        writeln!(self.writer_mut(), "{}", line)?;
+        Ok(())
+    }
+
+    fn csv_header(&self, len: usize, scalar: bool) -> String {
+        let names = match &self.header {
+            Some(names) => names.iter().map(|n| csv_escape(n)).collect(),
+            None if scalar => vec!["y".to_string()],
+            None => (0..len).map(|i| format!("y{}", i)).collect::<Vec<_>>(),
+        };
+        format!("t,{}", names.join(","))
+    }
+}
+
+impl<W: Write> Drop for Exporter<W> {
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.as_mut() {
+            let _ = writer.flush();
+        }
+    }
+}
+
+fn json_number(v: f64) -> String {
+    if v.is_finite() {
+        v.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Quotes fields holding a separator, quote or line break, doubling the
+// quotes inside (RFC 4180).
+fn csv_escape(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+impl<W: Write> SinkMut<f64> for Exporter<W> {
+    fn input_mut(&mut self, t: f64, value: &f64) {
+        self.write_row(t, &[*value], true);
+    }
+
+    fn reset_mut(&mut self) {
+        self.restart();
+    }
+}
+
+#[cfg(feature = "vector")]
+impl<W: Write> SinkMut<Vector> for Exporter<W> {
+    fn input_mut(&mut self, t: f64, value: &Vector) {
+        self.write_row(t, value.data(), false);
+    }
+
+    fn reset_mut(&mut self) {
+        self.restart();
+    }
+}
+
+#[cfg(feature = "matrix")]
+impl<W: Write> SinkMut<Matrix> for Exporter<W> {
+    fn input_mut(&mut self, t: f64, value: &Matrix) {
+        self.write_row(t, value.ravel(), false);
+    }
+
+    fn reset_mut(&mut self) {
+        self.restart();
+    }
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+
+    #[test]
+    fn test_csv() {
+        let mut e = Exporter::new(Vec::new(), Format::Csv);
+        e.input_mut(0.0, &1.5);
+        e.input_mut(0.5, &-2.0);
+        let out = String::from_utf8(e.finish().unwrap()).unwrap();
+        assert_eq!(out, "t,y\n0,1.5\n0.5,-2\n");
+
+        let mut e = Exporter::new(Vec::new(), Format::Csv);
+        e.set_header(vec!["a,b".to_string()]);
+        e.input_mut(0.0, &1.0);
+        let out = String::from_utf8(e.finish().unwrap()).unwrap();
+        assert_eq!(out, "t,\"a,b\"\n0,1\n");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(json_escape("a\"\n\u{1}"), "a\\\"\\n\\u0001");
+    }
+
+    #[test]
+    fn test_in_diagram() {
+        use crate::{
+            diagram::{Diagram, SinkBlock},
+            utils::fixtures::Constant,
+        };
+        use std::io::BufWriter;
+
+        let mut d = Diagram::new();
+        let unit = d.add_source("unit", Constant(1.0));
+        let sink = d.add_sink(
+            "export",
+            Exporter::new(BufWriter::new(Vec::new()), Format::Csv),
+        );
+        d.connect(unit, 0, sink, 0).unwrap();
+        d.step(0.0).unwrap();
+        d.step(1.0).unwrap();
+
+        let exporter = d
+            .get_mut::<SinkBlock<Exporter<BufWriter<Vec<u8>>>>>(sink)
+            .unwrap()
+            .inner_mut();
+        assert!(exporter.get_ref().get_ref().is_empty());
+        exporter.flush().unwrap();
+        assert_eq!(exporter.get_ref().get_ref(), b"t,y\n0,1\n1,1\n");
+
+        d.reset();
+        d.step(0.0).unwrap();
+        let exporter = d
+            .get_mut::<SinkBlock<Exporter<BufWriter<Vec<u8>>>>>(sink)
+            .unwrap()
+            .inner_mut();
+        exporter.flush().unwrap();
+        let out = exporter.get_ref().get_ref();
+        assert_eq!(out, b"t,y\n0,1\n1,1\nt,y\n0,1\n");
+    }
+
+    #[cfg(feature = "vector")]
+    #[test]
+    fn test_json_lines() {
+        let mut e = Exporter::new(Vec::new(), Format::JsonLines);
+        e.input_mut(0.0, &Vector::from(vec![1.0, f64::NAN]));
+        let out = String::from_utf8(e.finish().unwrap()).unwrap();
+        assert_eq!(out, "{\"t\":0,\"value\":[1,null]}\n");
+
+        let mut e = Exporter::new(Vec::new(), Format::JsonLines);
+        e.set_header(vec!["x".to_string(), "v".to_string()]);
+        e.input_mut(1.0, &Vector::from(vec![1.0, 2.0]));
+        e.input_mut(2.0, &Vector::from(vec![1.0, 2.0, 3.0]));
+        assert!(e.error().is_some());
+        assert!(e.finish().is_err());
+    }
+}
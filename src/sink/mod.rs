@@ -0,0 +1,4 @@
+pub(crate) mod export;
+pub(crate) mod recorder;
+pub use export::*;
+pub use recorder::*;
@@ -0,0 +1,238 @@
+use crate::{block::SinkMut, model::IsValue};
+use std::collections::VecDeque;
+
+#[cfg(feature = "matrix")]
+use crate::model::Matrix;
+#[cfg(feature = "vector")]
+use crate::model::Vector;
+
+#[derive(Debug, Clone)]
+pub struct Recorder<V: IsValue> {
+    times: VecDeque<f64>,
+    values: VecDeque<V>,
+    decimation: usize,
+    max_len: Option<usize>,
+    count: usize,
+}
+
+pub type Scope<V> = Recorder<V>;
+
+impl<V: IsValue> Recorder<V> {
+    // Keeps every `decimation`-th sample, and at most `max_len` samples when
+    // set, dropping the oldest first.
+    pub fn new(decimation: usize, max_len: Option<usize>) -> Self {
+        Self {
+            times: VecDeque::new(),
+            values: VecDeque::new(),
+            decimation: decimation.max(1),
+            max_len,
+            count: 0,
+        }
+    }
+
+    pub fn decimation(&self) -> usize {
+        self.decimation
+    }
+
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    pub fn times(&self) -> Vec<f64> {
+        self.times.iter().copied().collect()
+    }
+
+    pub fn values(&self) -> Vec<V> {
+        self.values.iter().cloned().collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (f64, &V)> {
+        self.times.iter().copied().zip(self.values.iter())
+    }
+
+    pub fn last(&self) -> Option<(f64, &V)> {
+        Some((*self.times.back()?, self.values.back()?))
+    }
+
+    pub fn clear(&mut self) {
+        self.times.clear();
+        self.values.clear();
+        self.count = 0;
+    }
+
+    fn push(&mut self, t: f64, value: &V) {
+        let keep = self.count.is_multiple_of(self.decimation);
+        self.count += 1;
+        if !keep || self.max_len == Some(0) {
+            return;
+        }
+        if self.max_len.is_some_and(|m| self.times.len() >= m) {
+            self.times.pop_front();
+            self.values.pop_front();
+        }
+        self.times.push_back(t);
+        self.values.push_back(value.clone());
+    }
+}
+
+impl<V: IsValue> Default for Recorder<V> {
+    fn default() -> Self {
+        Self::new(1, None)
+    }
+}
+
+impl SinkMut<f64> for Recorder<f64> {
+    fn input_mut(&mut self, t: f64, value: &f64) {
+        self.push(t, value);
+    }
+
+    fn reset_mut(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(feature = "vector")]
+impl SinkMut<Vector> for Recorder<Vector> {
+    fn input_mut(&mut self, t: f64, value: &Vector) {
+        self.push(t, value);
+    }
+
+    fn reset_mut(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(feature = "matrix")]
+impl SinkMut<Matrix> for Recorder<Matrix> {
+    fn input_mut(&mut self, t: f64, value: &Matrix) {
+        self.push(t, value);
+    }
+
+    fn reset_mut(&mut self) {
+        self.clear();
+    }
+}
+
+// The history has one row per sample, holding the time followed by the
+// recorded value (matrices are flattened row by row).
+#[cfg(feature = "matrix")]
+impl Recorder<f64> {
+    pub fn history(&self) -> Matrix {
+        Matrix::from(self.iter().map(|(t, v)| vec![t, *v]).collect::<Vec<_>>())
+    }
+}
+
+#[cfg(feature = "matrix")]
+impl Recorder<Vector> {
+    pub fn history(&self) -> Matrix {
+        Matrix::from(
+            self.iter()
+                .map(|(t, v)| {
+                    let mut row = vec![t];
+                    row.extend_from_slice(v.data());
+                    row
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+#[cfg(feature = "matrix")]
+impl Recorder<Matrix> {
+    pub fn history(&self) -> Matrix {
+        Matrix::from(
+            self.iter()
+                .map(|(t, v)| {
+                    let mut row = vec![t];
+                    row.extend_from_slice(v.ravel());
+                    row
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod sink_tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder() {
+        let mut r = Recorder::new(2, Some(3));
+        for k in 0..10 {
+            r.input_mut(k as f64, &(10.0 * k as f64));
+        }
+        assert_eq!(r.times(), vec![4.0, 6.0, 8.0]);
+        assert_eq!(r.values(), vec![40.0, 60.0, 80.0]);
+        assert_eq!(r.last(), Some((8.0, &80.0)));
+        #[cfg(feature = "matrix")]
+        assert_eq!(
+            r.history(),
+            Matrix::from(vec![vec![4.0, 40.0], vec![6.0, 60.0], vec![8.0, 80.0]])
+        );
+        r.clear();
+        assert!(r.is_empty());
+    }
+
+    #[cfg(feature = "matrix")]
+    #[test]
+    fn test_recorder_matrix() {
+        let mut v = Scope::default();
+        v.input_mut(0.0, &Vector::from(vec![1.0, 2.0]));
+        v.input_mut(0.5, &Vector::from(vec![3.0, 4.0]));
+        assert_eq!(
+            v.history(),
+            Matrix::from(vec![vec![0.0, 1.0, 2.0], vec![0.5, 3.0, 4.0]])
+        );
+
+        let mut m = Scope::default();
+        m.input_mut(1.0, &Matrix::from(vec![vec![1.0, 2.0], vec![3.0, 4.0]]));
+        assert_eq!(
+            m.history(),
+            Matrix::from(vec![vec![1.0, 1.0, 2.0, 3.0, 4.0]])
+        );
+    }
+
+    #[cfg(feature = "source")]
+    #[test]
+    fn test_recorder_in_diagram() {
+        use crate::{
+            diagram::{Diagram, SinkBlock},
+            simulation::Simulation,
+            source::Step,
+        };
+
+        let mut d = Diagram::new();
+        let step = d.add("step", Step::new(0.0, 1.0, 0.5));
+        let scope = d.add_sink("scope", Recorder::<f64>::default());
+        d.connect(step, 0, scope, 0).unwrap();
+
+        let mut sim = Simulation::new(d, 0.0, 1.0, 0.25);
+        sim.run().unwrap();
+        let r = sim
+            .diagram()
+            .get::<SinkBlock<Recorder<f64>>>(scope)
+            .unwrap()
+            .inner();
+        assert_eq!(r.values().first(), Some(&0.0));
+        assert_eq!(r.last(), Some((1.0, &1.0)));
+
+        // A second run starts from an empty history.
+        let len = r.len();
+        sim.run().unwrap();
+        let r = sim
+            .diagram()
+            .get::<SinkBlock<Recorder<f64>>>(scope)
+            .unwrap()
+            .inner();
+        assert_eq!(r.len(), len);
+    }
+}
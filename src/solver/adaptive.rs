@@ -0,0 +1,227 @@
+use super::{Derivative, Solver};
+use crate::utils::SimError;
+
+const C: [f64; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+const A: [[f64; 6]; 7] = [
+    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+        0.0,
+        0.0,
+    ],
+    [
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+        0.0,
+    ],
+    [
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ],
+];
+const B5: [f64; 7] = [
+    35.0 / 384.0,
+    0.0,
+    500.0 / 1113.0,
+    125.0 / 192.0,
+    -2187.0 / 6784.0,
+    11.0 / 84.0,
+    0.0,
+];
+const B4: [f64; 7] = [
+    5179.0 / 57600.0,
+    0.0,
+    7571.0 / 16695.0,
+    393.0 / 640.0,
+    -92097.0 / 339200.0,
+    187.0 / 2100.0,
+    1.0 / 40.0,
+];
+
+pub trait AdaptiveSolver: Solver {
+    // Returns the proposed state and its error estimate scaled by the
+    // tolerances, so that the step is acceptable when the error is <= 1.
+    fn attempt(&self, f: &mut Derivative, t: f64, x: &[f64], h: f64) -> (Vec<f64>, f64);
+
+    fn next_step(&self, h: f64, error: f64) -> f64;
+
+    fn min_step(&self) -> f64;
+
+    fn max_step(&self) -> f64;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DormandPrince {
+    rtol: f64,
+    atol: f64,
+    h_min: f64,
+    h_max: f64,
+}
+
+impl DormandPrince {
+    // The absolute tolerance must be positive so that the error scale never
+    // vanishes, and the step bounds must satisfy 0 < h_min <= h_max.
+    pub fn new(rtol: f64, atol: f64, h_min: f64, h_max: f64) -> Result<Self, SimError> {
+        if !(rtol >= 0.0 && rtol.is_finite() && atol > 0.0 && atol.is_finite()) {
+            return Err(SimError::InvalidParameter(format!(
+                "tolerances must be finite with rtol >= 0 and atol > 0, got rtol = {}, atol = {}",
+                rtol, atol
+            )));
+        }
+        if !(h_min > 0.0 && h_min <= h_max) {
+            return Err(SimError::InvalidParameter(format!(
+                "step bounds must satisfy 0 < h_min <= h_max, got h_min = {}, h_max = {}",
+                h_min, h_max
+            )));
+        }
+        Ok(Self {
+            rtol,
+            atol,
+            h_min,
+            h_max,
+        })
+    }
+
+    pub fn rtol(&self) -> f64 {
+        self.rtol
+    }
+
+    pub fn atol(&self) -> f64 {
+        self.atol
+    }
+
+    fn stages(&self, f: &mut Derivative, t: f64, x: &[f64], h: f64) -> Vec<Vec<f64>> {
+        let mut k: Vec<Vec<f64>> = Vec::with_capacity(7);
+        for s in 0..7 {
+            let xs = (0..x.len())
+                .map(|i| x[i] + h * (0..s).map(|j| A[s][j] * k[j][i]).sum::<f64>())
+                .collect::<Vec<_>>();
+            k.push(f(t + C[s] * h, &xs));
+        }
+        k
+    }
+}
+
+impl Default for DormandPrince {
+    fn default() -> Self {
+        Self {
+            rtol: 1e-6,
+            atol: 1e-9,
+            h_min: 1e-12,
+            h_max: f64::INFINITY,
+        }
+    }
+}
+
+impl Solver for DormandPrince {
+    fn step(&self, f: &mut Derivative, t: f64, x: &[f64], h: f64) -> Result<Vec<f64>, SimError> {
+        Ok(self.attempt(f, t, x, h).0)
+    }
+}
+
+impl AdaptiveSolver for DormandPrince {
+    fn attempt(&self, f: &mut Derivative, t: f64, x: &[f64], h: f64) -> (Vec<f64>, f64) {
+        let k = self.stages(f, t, x, h);
+        let mut y = Vec::with_capacity(x.len());
+        let mut sum = 0.0;
+        for i in 0..x.len() {
+            let y5 = x[i] + h * (0..7).map(|s| B5[s] * k[s][i]).sum::<f64>();
+            let e = h * (0..7).map(|s| (B5[s] - B4[s]) * k[s][i]).sum::<f64>();
+            let scale = self.atol + self.rtol * x[i].abs().max(y5.abs());
+            sum += (e / scale).powi(2);
+            y.push(y5);
+        }
+        let error = if x.is_empty() {
+            0.0
+        } else {
+            (sum / x.len() as f64).sqrt()
+        };
+        (y, error)
+    }
+
+    fn next_step(&self, h: f64, error: f64) -> f64 {
+        // A non-finite estimate, e.g. from a NaN derivative, is a rejection.
+        let factor = if !error.is_finite() {
+            0.2
+        } else if error == 0.0 {
+            5.0
+        } else {
+            (0.9 * error.powf(-0.2)).clamp(0.2, 5.0)
+        };
+        (h * factor).clamp(self.h_min, self.h_max)
+    }
+
+    fn min_step(&self) -> f64 {
+        self.h_min
+    }
+
+    fn max_step(&self) -> f64 {
+        self.h_max
+    }
+}
+
+#[cfg(test)]
+mod adaptive_tests {
+    use super::*;
+
+    #[test]
+    fn test_dormand_prince_order() {
+        let solver = DormandPrince::default();
+        let mut f = |_t: f64, x: &[f64]| vec![-x[0]];
+        let error = |h: f64, f: &mut Derivative| {
+            let mut x = vec![1.0];
+            let steps = (1.0 / h).round() as usize;
+            for k in 0..steps {
+                x = solver.step(f, k as f64 * h, &x, h).unwrap();
+            }
+            (x[0] - (-1.0f64).exp()).abs()
+        };
+        let ratio = error(0.1, &mut f) / error(0.05, &mut f);
+        assert!((ratio.log2() - 5.0).abs() < 0.3);
+    }
+
+    #[test]
+    fn test_error_estimate() {
+        let solver = DormandPrince::new(1e-6, 1e-6, 1e-12, 1.0).unwrap();
+        let mut f = |_t: f64, x: &[f64]| vec![-x[0]];
+        let (_, small) = solver.attempt(&mut f, 0.0, &[1.0], 0.01);
+        let (_, large) = solver.attempt(&mut f, 0.0, &[1.0], 1.0);
+        assert!(small < 1.0);
+        assert!(large > 1.0);
+        assert!(solver.next_step(1.0, large) < 1.0);
+        assert_eq!(solver.next_step(0.5, 0.0), 1.0);
+        assert_eq!(solver.next_step(0.5, f64::NAN), 0.1);
+        assert_eq!(solver.next_step(0.5, f64::INFINITY), 0.1);
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        for (rtol, atol, h_min, h_max) in [
+            (0.0, 0.0, 1e-12, 1.0),
+            (-1e-6, 1e-9, 1e-12, 1.0),
+            (1e-6, f64::NAN, 1e-12, 1.0),
+            (1e-6, 1e-9, 1.0, 0.5),
+            (1e-6, 1e-9, 0.0, 1.0),
+            (1e-6, 1e-9, 1e-12, f64::NAN),
+        ] {
+            assert!(matches!(
+                DormandPrince::new(rtol, atol, h_min, h_max),
+                Err(SimError::InvalidParameter(_))
+            ));
+        }
+        assert!(DormandPrince::new(0.0, 1e-9, 1e-12, f64::INFINITY).is_ok());
+    }
+}
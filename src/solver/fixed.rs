@@ -0,0 +1,226 @@
+use super::{Derivative, Solver};
+use crate::utils::SimError;
+
+pub(crate) fn axpy(x: &[f64], h: f64, dx: &[f64]) -> Vec<f64> {
+    x.iter().zip(dx.iter()).map(|(x, dx)| x + h * dx).collect()
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Euler;
+
+impl Solver for Euler {
+    fn step(&self, f: &mut Derivative, t: f64, x: &[f64], h: f64) -> Result<Vec<f64>, SimError> {
+        let k1 = f(t, x);
+        Ok(axpy(x, h, &k1))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Heun;
+
+impl Solver for Heun {
+    fn step(&self, f: &mut Derivative, t: f64, x: &[f64], h: f64) -> Result<Vec<f64>, SimError> {
+        let k1 = f(t, x);
+        let k2 = f(t + h, &axpy(x, h, &k1));
+        Ok(x.iter()
+            .zip(k1.iter().zip(k2.iter()))
+            .map(|(x, (k1, k2))| x + h * 0.5 * (k1 + k2))
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rk4;
+
+impl Solver for Rk4 {
+    fn step(&self, f: &mut Derivative, t: f64, x: &[f64], h: f64) -> Result<Vec<f64>, SimError> {
+        let k1 = f(t, x);
+        let k2 = f(t + 0.5 * h, &axpy(x, 0.5 * h, &k1));
+        let k3 = f(t + 0.5 * h, &axpy(x, 0.5 * h, &k2));
+        let k4 = f(t + h, &axpy(x, h, &k3));
+        Ok((0..x.len())
+            .map(|i| x[i] + h / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]))
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BackwardEuler {
+    tolerance: f64,
+    max_iterations: usize,
+}
+
+impl BackwardEuler {
+    pub const fn new(tolerance: f64, max_iterations: usize) -> Self {
+        Self {
+            tolerance,
+            max_iterations,
+        }
+    }
+
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+}
+
+impl Default for BackwardEuler {
+    fn default() -> Self {
+        Self::new(1e-10, 50)
+    }
+}
+
+impl Solver for BackwardEuler {
+    // Newton iteration on y - x - h f(t + h, y) = 0 with a finite difference
+    // Jacobian, starting from the forward Euler prediction.
+    fn step(&self, f: &mut Derivative, t: f64, x: &[f64], h: f64) -> Result<Vec<f64>, SimError> {
+        let n = x.len();
+        let t1 = t + h;
+        let mut y = axpy(x, h, &f(t, x));
+        for _ in 0..self.max_iterations {
+            let fy = f(t1, &y);
+            let residual = (0..n).map(|i| y[i] - x[i] - h * fy[i]).collect::<Vec<_>>();
+
+            let mut jacobian = vec![vec![0.0; n]; n];
+            for j in 0..n {
+                let eps = 1e-8 * y[j].abs().max(1.0);
+                let mut yj = y.clone();
+                yj[j] += eps;
+                let fj = f(t1, &yj);
+                for i in 0..n {
+                    let df = (fj[i] - fy[i]) / eps;
+                    jacobian[i][j] = if i == j { 1.0 } else { 0.0 } - h * df;
+                }
+            }
+
+            let delta = solve_dense(jacobian, residual).ok_or_else(|| {
+                SimError::SolverFailure(format!("singular Newton Jacobian at t = {}", t1))
+            })?;
+            let mut size = 0.0f64;
+            for i in 0..n {
+                y[i] -= delta[i];
+                size = size.max(delta[i].abs() / y[i].abs().max(1.0));
+            }
+            if size < self.tolerance {
+                return Ok(y);
+            }
+        }
+        Err(SimError::SolverFailure(format!(
+            "Newton iteration did not converge in {} iterations at t = {}",
+            self.max_iterations, t1
+        )))
+    }
+}
+
+// Gaussian elimination with partial pivoting, None if `a` is singular.
+pub(crate) fn solve_dense(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for k in 0..n {
+        let pivot = (k..n).max_by(|i, j| a[*i][k].abs().total_cmp(&a[*j][k].abs()))?;
+        if a[pivot][k] == 0.0 {
+            return None;
+        }
+        a.swap(k, pivot);
+        b.swap(k, pivot);
+        let (upper, lower) = a.split_at_mut(k + 1);
+        let row = &upper[k];
+        for (i, other) in lower.iter_mut().enumerate() {
+            let factor = other[k] / row[k];
+            other[k..]
+                .iter_mut()
+                .zip(row[k..].iter())
+                .for_each(|(a, r)| *a -= factor * r);
+            b[k + 1 + i] -= factor * b[k];
+        }
+    }
+    for k in (0..n).rev() {
+        let sum = (k + 1..n).map(|j| a[k][j] * b[j]).sum::<f64>();
+        b[k] = (b[k] - sum) / a[k][k];
+    }
+    Some(b)
+}
+
+#[cfg(test)]
+mod solver_tests {
+    use super::*;
+
+    fn run(solver: &dyn Solver, f: &mut Derivative, x: &[f64], t_end: f64, h: f64) -> Vec<f64> {
+        let steps = (t_end / h).round() as usize;
+        let mut x = x.to_vec();
+        for k in 0..steps {
+            x = solver.step(f, k as f64 * h, &x, h).unwrap();
+        }
+        x
+    }
+
+    fn decay_error(solver: &dyn Solver, h: f64) -> f64 {
+        let x = run(solver, &mut |_t, x| vec![-x[0]], &[1.0], 1.0, h);
+        (x[0] - (-1.0f64).exp()).abs()
+    }
+
+    #[test]
+    fn test_exponential_decay_order() {
+        let orders = [
+            (&Euler as &dyn Solver, 1.0),
+            (&Heun, 2.0),
+            (&Rk4, 4.0),
+            (&BackwardEuler::default(), 1.0),
+        ];
+        for (solver, order) in orders {
+            let ratio = decay_error(solver, 0.02) / decay_error(solver, 0.01);
+            assert!(
+                (ratio.log2() - order).abs() < 0.1,
+                "{:?} converges with order {}",
+                solver,
+                ratio.log2()
+            );
+        }
+        assert!(decay_error(&Rk4, 0.01) < 1e-9);
+    }
+
+    #[test]
+    fn test_harmonic_oscillator() {
+        let period = 2.0 * std::f64::consts::PI;
+        let mut f = |_t: f64, x: &[f64]| vec![x[1], -x[0]];
+        let x = run(&Rk4, &mut f, &[1.0, 0.0], period, period / 1000.0);
+        assert!((x[0] - 1.0).abs() < 1e-10);
+        assert!(x[1].abs() < 1e-10);
+
+        let x = run(&Heun, &mut f, &[1.0, 0.0], period, period / 1000.0);
+        assert!((x[0] - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_stiff_backward_euler() {
+        let mut f = |t: f64, x: &[f64]| vec![-1000.0 * (x[0] - t.cos())];
+        let x = run(&BackwardEuler::default(), &mut f, &[0.0], 1.0, 0.01);
+        assert!((x[0] - 1.0f64.cos()).abs() < 1e-2);
+
+        let x = run(&Euler, &mut f, &[0.0], 1.0, 0.01);
+        assert!(!x[0].is_finite() || x[0].abs() > 1e3);
+    }
+
+    #[test]
+    fn test_backward_euler_failure() {
+        // I - h df/dx vanishes for f = 2x and h = 0.5.
+        let mut f = |_t: f64, x: &[f64]| vec![2.0 * x[0]];
+        let solver = BackwardEuler::default();
+        assert!(matches!(
+            solver.step(&mut f, 0.0, &[1.0], 0.5),
+            Err(SimError::SolverFailure(_))
+        ));
+
+        let solver = BackwardEuler::new(1e-10, 1);
+        let mut f = |_t: f64, x: &[f64]| vec![-x[0].powi(3)];
+        assert!(matches!(
+            solver.step(&mut f, 0.0, &[1.0], 1.0),
+            Err(SimError::SolverFailure(_))
+        ));
+        assert!(BackwardEuler::new(1e-10, 50)
+            .step(&mut f, 0.0, &[1.0], 1.0)
+            .is_ok());
+    }
+}
@@ -0,0 +1,54 @@
+pub(crate) mod adaptive;
+pub(crate) mod fixed;
+
+pub use adaptive::*;
+pub use fixed::*;
+
+use crate::utils::SimError;
+use std::fmt::Debug;
+
+pub type Derivative<'a> = dyn FnMut(f64, &[f64]) -> Vec<f64> + 'a;
+
+pub trait Solver: Debug {
+    // Fails with `SimError::SolverFailure` when an implicit method cannot
+    // solve for the next state.
+    fn step(&self, f: &mut Derivative, t: f64, x: &[f64], h: f64) -> Result<Vec<f64>, SimError>;
+}
+
+// Advances `x` by integrating an input sampled as `u0` at `t0` and `u1` at
+// `t1`, linearly interpolated in between.
+#[cfg(feature = "continuous")]
+pub(crate) fn integrate_input(
+    solver: &dyn Solver,
+    x: &[f64],
+    (t0, u0): (f64, &[f64]),
+    (t1, u1): (f64, &[f64]),
+) -> Result<Vec<f64>, SimError> {
+    integrate_system(solver, x, (t0, u0), (t1, u1), |_x, u| u.to_vec())
+}
+
+// Advances the system `dx = f(x, u)` with the input interpolated as in
+// `integrate_input`.
+#[cfg(feature = "continuous")]
+pub(crate) fn integrate_system(
+    solver: &dyn Solver,
+    x: &[f64],
+    (t0, u0): (f64, &[f64]),
+    (t1, u1): (f64, &[f64]),
+    f: impl Fn(&[f64], &[f64]) -> Vec<f64>,
+) -> Result<Vec<f64>, SimError> {
+    let h = t1 - t0;
+    if h == 0.0 {
+        return Ok(x.to_vec());
+    }
+    let mut f = |t: f64, x: &[f64]| {
+        let s = (t - t0) / h;
+        let u = u0
+            .iter()
+            .zip(u1.iter())
+            .map(|(a, b)| a + s * (b - a))
+            .collect::<Vec<_>>();
+        f(x, &u)
+    };
+    solver.step(&mut f, t0, x, h)
+}
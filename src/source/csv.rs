@@ -0,0 +1,184 @@
+use crate::{block::Source, utils::SimError};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+#[cfg(feature = "vector")]
+use crate::model::Vector;
+
+// Replays a table of samples whose first column is time. Columns are linearly
+// interpolated between rows and held at the first and last rows outside the
+// recorded range.
+#[derive(Debug, Clone)]
+pub struct CsvSource {
+    names: Vec<String>,
+    times: Vec<f64>,
+    rows: Vec<Vec<f64>>,
+}
+
+impl CsvSource {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, SimError> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    // A first line that does not parse as numbers is taken as the header.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, SimError> {
+        let mut names = Vec::new();
+        let mut times: Vec<f64> = Vec::new();
+        let mut rows = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let fields = line.split(',').map(|f| f.trim()).collect::<Vec<_>>();
+            let values = fields
+                .iter()
+                .map(|f| f.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>();
+            let values = match values {
+                Ok(values) => values,
+                Err(_) if times.is_empty() && names.is_empty() => {
+                    names = fields[1..].iter().map(|f| f.to_string()).collect();
+                    continue;
+                }
+                Err(e) => {
+                    return Err(SimError::Parse {
+                        line: i + 1,
+                        message: e.to_string(),
+                    })
+                }
+            };
+            if values.len() < 2 {
+                return Err(SimError::Parse {
+                    line: i + 1,
+                    message: "expected a time column and at least one value".to_string(),
+                });
+            }
+            if let Some(first) = rows.first().map(|r: &Vec<f64>| r.len()) {
+                if values.len() - 1 != first {
+                    return Err(SimError::Parse {
+                        line: i + 1,
+                        message: format!("expected {} values, found {}", first, values.len() - 1),
+                    });
+                }
+            }
+            if !values[0].is_finite() {
+                return Err(SimError::Parse {
+                    line: i + 1,
+                    message: format!("time must be finite, found {}", values[0]),
+                });
+            }
+            if times.last().is_some_and(|last| values[0] <= *last) {
+                return Err(SimError::Parse {
+                    line: i + 1,
+                    message: "time must be strictly increasing".to_string(),
+                });
+            }
+            times.push(values[0]);
+            rows.push(values[1..].to_vec());
+        }
+        if rows.is_empty() {
+            return Err(SimError::InvalidParameter(
+                "table contains no samples".to_string(),
+            ));
+        }
+        if !names.is_empty() && names.len() != rows[0].len() {
+            return Err(SimError::DimensionMismatch {
+                expected: rows[0].len(),
+                found: names.len(),
+            });
+        }
+        Ok(Self { names, times, rows })
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.rows[0].len()
+    }
+
+    pub fn sample(&self, t: f64) -> Vec<f64> {
+        let last = self.times.len() - 1;
+        if t <= self.times[0] {
+            return self.rows[0].clone();
+        }
+        if t >= self.times[last] {
+            return self.rows[last].clone();
+        }
+        let i = self.times.partition_point(|x| *x <= t) - 1;
+        let s = (t - self.times[i]) / (self.times[i + 1] - self.times[i]);
+        self.rows[i]
+            .iter()
+            .zip(self.rows[i + 1].iter())
+            .map(|(a, b)| a + s * (b - a))
+            .collect()
+    }
+}
+
+impl Source<f64> for CsvSource {
+    fn output(&self, t: f64) -> f64 {
+        self.sample(t)[0]
+    }
+}
+
+#[cfg(feature = "vector")]
+impl Source<Vector> for CsvSource {
+    fn output(&self, t: f64) -> Vector {
+        Vector::from(self.sample(t))
+    }
+}
+
+#[cfg(test)]
+mod csv_tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_source() {
+        let data = "t,x,v\n0,0,1\n1,2,1\n\n3,2,-1\n";
+        let s = CsvSource::from_reader(data.as_bytes()).unwrap();
+        assert_eq!(s.names(), &["x".to_string(), "v".to_string()]);
+        assert_eq!(Source::<f64>::output(&s, -1.0), 0.0);
+        assert_eq!(Source::<f64>::output(&s, 0.5), 1.0);
+        assert_eq!(s.sample(2.0), vec![2.0, 0.0]);
+        assert_eq!(s.sample(5.0), vec![2.0, -1.0]);
+
+        let err = CsvSource::from_reader("0,1\n0,2\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, SimError::Parse { line: 2, .. }));
+        for data in ["0,1\nnan,2\n", "0,1\ninf,2\n"] {
+            let err = CsvSource::from_reader(data.as_bytes()).unwrap_err();
+            assert!(matches!(err, SimError::Parse { line: 2, .. }));
+        }
+        let err = CsvSource::from_reader("0,1\n1,x\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, SimError::Parse { line: 2, .. }));
+    }
+
+    #[cfg(all(feature = "vector", feature = "sink"))]
+    #[test]
+    fn test_round_trip() {
+        use crate::{
+            block::SinkMut,
+            sink::{Exporter, Format},
+        };
+
+        let mut e = Exporter::new(Vec::new(), Format::Csv);
+        for k in 0..5 {
+            let t = k as f64 * 0.25;
+            e.input_mut(t, &Vector::from(vec![t, t * t]));
+        }
+        let out = e.finish().unwrap();
+        let s = CsvSource::from_reader(out.as_slice()).unwrap();
+        let v: Vector = s.output(0.625);
+        assert!((v[0] - 0.625).abs() < 1e-12);
+        assert!((v[1] - 0.40625).abs() < 1e-12);
+    }
+}
@@ -1,2 +1,11 @@
+pub(crate) mod csv;
+#[cfg(feature = "random")]
+pub(crate) mod random;
+#[allow(clippy::module_inception)]
 pub(crate) mod source;
+pub use csv::*;
+#[cfg(feature = "random")]
+pub use random::*;
+#[cfg(feature = "vector")]
+pub use source::vector::VectorSource;
 pub use source::*;
@@ -0,0 +1,379 @@
+use crate::block::SourceMut;
+use crate::utils::SimError;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[cfg(feature = "vector")]
+use crate::model::Vector;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoiseKind {
+    Uniform { min: f64, max: f64 },
+    Gaussian { mean: f64, std_dev: f64 },
+    // `std_dev` is the stationary standard deviation of the output.
+    Pink { std_dev: f64 },
+    // A random walk whose increments have standard deviation `step_std_dev`.
+    Brown { step_std_dev: f64 },
+}
+
+// Paul Kellet's economy filter, roughly -3 dB/octave above 1e-4 of the sample
+// rate: three one-pole sections driven by the same white input plus a direct
+// term.
+const PINK_POLES: [f64; 3] = [0.99765, 0.96300, 0.57000];
+const PINK_GAINS: [f64; 3] = [0.0990460, 0.2965164, 1.0526913];
+const PINK_DIRECT: f64 = 0.1848;
+
+// Stationary standard deviation of the filter for unit white input, the root
+// of the summed squared impulse response.
+fn pink_std_dev() -> f64 {
+    let h0 = PINK_GAINS.iter().sum::<f64>() + PINK_DIRECT;
+    let mut var = h0 * h0;
+    for i in 0..3 {
+        for j in 0..3 {
+            let a = PINK_POLES[i] * PINK_POLES[j];
+            var += PINK_GAINS[i] * PINK_GAINS[j] * a / (1.0 - a);
+        }
+    }
+    var.sqrt()
+}
+
+// SplitMix64 finaliser, used to decorrelate seeds before mixing in the index.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// A seeded random signal held constant over each sample period. The white
+// draw for sample `floor(t / sample_time)` depends only on the seed and that
+// index, so uniform and Gaussian noise can be sampled at any time in any
+// order. Pink and brown noise filter those draws from rest, starting at the
+// first sample requested; later samples step the filter through every index in
+// between and earlier ones replay it from the start. Either way a given seed
+// and start time yield the same signal whatever step size the solver takes.
+#[derive(Debug, Clone)]
+pub struct Noise {
+    kind: NoiseKind,
+    sample_time: f64,
+    seed: u64,
+    start: Option<i64>,
+    index: Option<i64>,
+    value: f64,
+    filter: [f64; 3],
+}
+
+impl Noise {
+    pub fn new(kind: NoiseKind, sample_time: f64, seed: u64) -> Result<Self, SimError> {
+        if !(sample_time > 0.0 && sample_time.is_finite()) {
+            return Err(SimError::InvalidParameter(format!(
+                "noise sample time must be positive and finite, got {sample_time}"
+            )));
+        }
+        let valid = match kind {
+            NoiseKind::Uniform { min, max } => min.is_finite() && max.is_finite() && min <= max,
+            NoiseKind::Gaussian { mean, std_dev } => {
+                mean.is_finite() && std_dev >= 0.0 && std_dev.is_finite()
+            }
+            NoiseKind::Pink { std_dev } => std_dev >= 0.0 && std_dev.is_finite(),
+            NoiseKind::Brown { step_std_dev } => step_std_dev >= 0.0 && step_std_dev.is_finite(),
+        };
+        if !valid {
+            return Err(SimError::InvalidParameter(format!(
+                "invalid noise parameters {kind:?}"
+            )));
+        }
+        Ok(Self {
+            kind,
+            sample_time,
+            seed,
+            start: None,
+            index: None,
+            value: 0.0,
+            filter: [0.0; 3],
+        })
+    }
+
+    pub fn uniform(min: f64, max: f64, sample_time: f64, seed: u64) -> Result<Self, SimError> {
+        Self::new(NoiseKind::Uniform { min, max }, sample_time, seed)
+    }
+
+    pub fn gaussian(
+        mean: f64,
+        std_dev: f64,
+        sample_time: f64,
+        seed: u64,
+    ) -> Result<Self, SimError> {
+        Self::new(NoiseKind::Gaussian { mean, std_dev }, sample_time, seed)
+    }
+
+    // White noise of the given power spectral density, band-limited by the
+    // sample-and-hold, so its variance is `power / sample_time`.
+    pub fn band_limited(power: f64, sample_time: f64, seed: u64) -> Result<Self, SimError> {
+        if power.is_nan() || power < 0.0 {
+            return Err(SimError::InvalidParameter(format!(
+                "noise power must be non-negative, got {power}"
+            )));
+        }
+        let std_dev = (power / sample_time).sqrt();
+        Self::gaussian(0.0, std_dev, sample_time, seed)
+    }
+
+    pub fn pink(std_dev: f64, sample_time: f64, seed: u64) -> Result<Self, SimError> {
+        Self::new(NoiseKind::Pink { std_dev }, sample_time, seed)
+    }
+
+    pub fn brown(step_std_dev: f64, sample_time: f64, seed: u64) -> Result<Self, SimError> {
+        Self::new(NoiseKind::Brown { step_std_dev }, sample_time, seed)
+    }
+
+    pub fn kind(&self) -> NoiseKind {
+        self.kind
+    }
+
+    pub fn sample_time(&self) -> f64 {
+        self.sample_time
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn reset(&mut self) {
+        self.start = None;
+        self.index = None;
+        self.value = 0.0;
+        self.filter = [0.0; 3];
+    }
+
+    fn rng(&self, index: i64) -> StdRng {
+        StdRng::seed_from_u64(splitmix64(self.seed) ^ index as u64)
+    }
+
+    fn normal(&self, index: i64) -> f64 {
+        // Box-Muller; `1 - u` keeps the logarithm away from zero.
+        let mut rng = self.rng(index);
+        let u1: f64 = 1.0 - rng.gen::<f64>();
+        let u2: f64 = rng.gen();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    fn filter(&mut self, index: i64) -> f64 {
+        let w = self.normal(index);
+        match self.kind {
+            NoiseKind::Pink { std_dev } => {
+                let b = &mut self.filter;
+                for i in 0..3 {
+                    b[i] = PINK_POLES[i] * b[i] + PINK_GAINS[i] * w;
+                }
+                std_dev * (b.iter().sum::<f64>() + PINK_DIRECT * w) / pink_std_dev()
+            }
+            NoiseKind::Brown { step_std_dev } => {
+                self.filter[0] += step_std_dev * w;
+                self.filter[0]
+            }
+            _ => unreachable!("only pink and brown noise are filtered"),
+        }
+    }
+
+    // Samples before the start of a filtered signal hold its first value.
+    fn filtered(&mut self, index: i64) -> f64 {
+        let start = *self.start.get_or_insert(index);
+        let index = index.max(start);
+        if self.index.is_some_and(|current| current > index) {
+            self.index = None;
+            self.filter = [0.0; 3];
+        }
+        while self.index != Some(index) {
+            let next = self.index.map_or(start, |current| current + 1);
+            self.value = self.filter(next);
+            self.index = Some(next);
+        }
+        self.value
+    }
+
+    pub fn sample(&mut self, t: f64) -> f64 {
+        let index = (t / self.sample_time).floor() as i64;
+        match self.kind {
+            NoiseKind::Uniform { min, max } => min + (max - min) * self.rng(index).gen::<f64>(),
+            NoiseKind::Gaussian { mean, std_dev } => mean + std_dev * self.normal(index),
+            NoiseKind::Pink { .. } | NoiseKind::Brown { .. } => self.filtered(index),
+        }
+    }
+}
+
+impl SourceMut<f64> for Noise {
+    fn output_mut(&mut self, t: f64) -> f64 {
+        self.sample(t)
+    }
+}
+
+// Independent channels seeded from `seed`, `seed + 1`, ...
+#[cfg(feature = "vector")]
+#[derive(Debug, Clone)]
+pub struct VectorNoise {
+    channels: Vec<Noise>,
+}
+
+#[cfg(feature = "vector")]
+impl VectorNoise {
+    pub fn new(kind: NoiseKind, dim: usize, sample_time: f64, seed: u64) -> Result<Self, SimError> {
+        Ok(Self {
+            channels: (0..dim as u64)
+                .map(|i| Noise::new(kind, sample_time, seed.wrapping_add(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn channels(&self) -> &[Noise] {
+        &self.channels
+    }
+
+    pub fn reset(&mut self) {
+        self.channels.iter_mut().for_each(|c| c.reset());
+    }
+}
+
+#[cfg(feature = "vector")]
+impl SourceMut<Vector> for VectorNoise {
+    fn output_mut(&mut self, t: f64) -> Vector {
+        Vector::from(
+            self.channels
+                .iter_mut()
+                .map(|c| c.sample(t))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod random_tests {
+    use super::*;
+
+    fn samples(noise: &mut Noise, n: usize) -> Vec<f64> {
+        (0..n)
+            .map(|k| noise.sample(k as f64 * noise.sample_time()))
+            .collect()
+    }
+
+    fn statistics(noise: &mut Noise, n: usize) -> (f64, f64) {
+        let x = samples(noise, n);
+        let mean = x.iter().sum::<f64>() / n as f64;
+        let var = x.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64;
+        (mean, var)
+    }
+
+    // Least-squares slope of log2 power against log2 frequency, from
+    // Hann-windowed periodograms averaged over segments of 1024 samples.
+    fn spectral_slope(noise: &mut Noise) -> f64 {
+        const LEN: usize = 1024;
+        let x = samples(noise, 4096 + 16 * LEN);
+        let window = (0..LEN)
+            .map(|n| 0.5 - 0.5 * (2.0 * std::f64::consts::PI * n as f64 / LEN as f64).cos())
+            .collect::<Vec<_>>();
+        let points = [8, 16, 32, 64, 128]
+            .iter()
+            .map(|&k| {
+                let mut power = 0.0;
+                for segment in x[4096..].chunks(LEN) {
+                    for bin in k - 1..=k + 1 {
+                        let w = 2.0 * std::f64::consts::PI * bin as f64 / LEN as f64;
+                        let (mut re, mut im) = (0.0, 0.0);
+                        for (n, v) in segment.iter().enumerate() {
+                            re += window[n] * v * (w * n as f64).cos();
+                            im -= window[n] * v * (w * n as f64).sin();
+                        }
+                        power += re * re + im * im;
+                    }
+                }
+                ((k as f64).log2(), power.log2())
+            })
+            .collect::<Vec<_>>();
+        let n = points.len() as f64;
+        let mx = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let my = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let sxy = points.iter().map(|p| (p.0 - mx) * (p.1 - my)).sum::<f64>();
+        let sxx = points.iter().map(|p| (p.0 - mx).powi(2)).sum::<f64>();
+        sxy / sxx
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let mut a = Noise::gaussian(0.0, 1.0, 0.1, 7).unwrap();
+        let mut b = Noise::gaussian(0.0, 1.0, 0.1, 7).unwrap();
+        // Coarse steps on `b` must not change the sequence.
+        let fine = (0..100)
+            .map(|k| a.sample(k as f64 * 0.05))
+            .collect::<Vec<_>>();
+        assert_eq!(b.sample(0.0), fine[0]);
+        assert_eq!(b.sample(4.95), fine[99]);
+        assert_eq!(b.sample(2.0), fine[40]);
+        a.reset();
+        assert_eq!(a.sample(0.0), fine[0]);
+        assert_eq!(a.sample(0.09), fine[0]);
+        let far = a.sample(1e12);
+        assert_eq!(Noise::gaussian(0.0, 1.0, 0.1, 7).unwrap().sample(1e12), far);
+        let other = Noise::gaussian(0.0, 1.0, 0.1, 8).unwrap().sample(0.0);
+        assert_ne!(other, fine[0]);
+
+        let mut brown = Noise::brown(1.0, 0.1, 7).unwrap();
+        let walk = (0..50)
+            .map(|k| brown.sample(k as f64 * 0.1))
+            .collect::<Vec<_>>();
+        assert_eq!(brown.sample(2.0), walk[20]);
+        assert_eq!(brown.sample(4.9), walk[49]);
+        assert_eq!(walk[1] - walk[0], fine[2]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        for sample_time in [0.0, -0.1, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                Noise::gaussian(0.0, 1.0, sample_time, 1),
+                Err(SimError::InvalidParameter(_))
+            ));
+        }
+        assert!(Noise::uniform(1.0, 0.0, 0.1, 1).is_err());
+        assert!(Noise::gaussian(0.0, -1.0, 0.1, 1).is_err());
+        assert!(Noise::band_limited(-1.0, 0.1, 1).is_err());
+        assert!(Noise::pink(f64::NAN, 0.1, 1).is_err());
+        assert!(Noise::brown(-1.0, 0.1, 1).is_err());
+    }
+
+    #[test]
+    fn test_statistics() {
+        let (mean, var) = statistics(&mut Noise::uniform(-1.0, 3.0, 1.0, 1).unwrap(), 20000);
+        assert!((mean - 1.0).abs() < 0.05);
+        assert!((var - 16.0 / 12.0).abs() < 0.05);
+        let (mean, var) = statistics(&mut Noise::band_limited(0.1, 0.01, 2).unwrap(), 20000);
+        assert!(mean.abs() < 0.1);
+        assert!((var - 10.0).abs() < 0.5);
+        let mut pink = Noise::pink(2.0, 1.0, 3).unwrap();
+        samples(&mut pink, 5000);
+        let (_, var) = statistics(&mut pink, 50000);
+        assert!((var - 4.0).abs() < 0.4, "pink variance {}", var);
+    }
+
+    #[test]
+    fn test_spectrum() {
+        // Power falls by 0, 3 and 6 dB per octave.
+        let white = spectral_slope(&mut Noise::gaussian(0.0, 1.0, 1.0, 4).unwrap());
+        assert!(white.abs() < 0.2, "white slope {}", white);
+        let pink = spectral_slope(&mut Noise::pink(1.0, 1.0, 5).unwrap());
+        assert!((pink + 1.0).abs() < 0.2, "pink slope {}", pink);
+        let brown = spectral_slope(&mut Noise::brown(1.0, 1.0, 6).unwrap());
+        assert!((brown + 2.0).abs() < 0.3, "brown slope {}", brown);
+    }
+
+    #[cfg(feature = "vector")]
+    #[test]
+    fn test_vector_noise() {
+        let kind = NoiseKind::Brown { step_std_dev: 1.0 };
+        let mut v = VectorNoise::new(kind, 3, 0.5, 11).unwrap();
+        let x = v.output_mut(1.0);
+        assert_eq!(x.dim(), 3);
+        assert_ne!(x[0], x[1]);
+        let mut c = Noise::brown(1.0, 0.5, 12).unwrap();
+        assert_eq!(c.sample(1.0), x[1]);
+        assert!(VectorNoise::new(kind, 3, 0.0, 11).is_err());
+    }
+}
@@ -1,4 +1,8 @@
-use crate::block::Source;
+use crate::{
+    block::{Block, Source},
+    utils::SimError,
+};
+use std::f64::consts::PI;
 
 #[derive(Debug, Clone)]
 pub struct Step {
@@ -37,4 +41,621 @@ impl Source<f64> for Step {
             self.end
         }
     }
+
+    fn zero_crossings(&self, t: f64) -> Vec<f64> {
+        vec![t - self.step_time]
+    }
+}
+
+impl Block<f64> for Step {
+    fn num_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        vec![Source::output(self, t)]
+    }
+
+    fn zero_crossings(&self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        Source::zero_crossings(self, t)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Ramp {
+    slope: f64,
+    start_time: f64,
+    init: f64,
+}
+
+impl Ramp {
+    pub fn new(slope: f64, start_time: f64, init: f64) -> Self {
+        Self {
+            slope,
+            start_time,
+            init,
+        }
+    }
+
+    pub fn slope(&self) -> f64 {
+        self.slope
+    }
+
+    pub fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    pub fn init(&self) -> f64 {
+        self.init
+    }
+}
+
+impl Source<f64> for Ramp {
+    fn output(&self, t: f64) -> f64 {
+        self.init + self.slope * (t - self.start_time).max(0.0)
+    }
+
+    fn zero_crossings(&self, t: f64) -> Vec<f64> {
+        vec![t - self.start_time]
+    }
+}
+
+impl Block<f64> for Ramp {
+    fn num_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        vec![Source::output(self, t)]
+    }
+
+    fn zero_crossings(&self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        Source::zero_crossings(self, t)
+    }
+}
+
+// Frequencies are in Hz and phases in radians for all periodic sources.
+#[derive(Debug, Clone)]
+pub struct Sine {
+    amplitude: f64,
+    frequency: f64,
+    phase: f64,
+    bias: f64,
+}
+
+impl Sine {
+    pub fn new(amplitude: f64, frequency: f64, phase: f64, bias: f64) -> Self {
+        Self {
+            amplitude,
+            frequency,
+            phase,
+            bias,
+        }
+    }
+
+    pub fn amplitude(&self) -> f64 {
+        self.amplitude
+    }
+
+    pub fn frequency(&self) -> f64 {
+        self.frequency
+    }
+
+    pub fn phase(&self) -> f64 {
+        self.phase
+    }
+
+    pub fn bias(&self) -> f64 {
+        self.bias
+    }
+}
+
+impl Source<f64> for Sine {
+    fn output(&self, t: f64) -> f64 {
+        self.bias + self.amplitude * (2.0 * PI * self.frequency * t + self.phase).sin()
+    }
+}
+
+impl Block<f64> for Sine {
+    fn num_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        vec![Source::output(self, t)]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Square {
+    amplitude: f64,
+    frequency: f64,
+    phase: f64,
+    bias: f64,
+}
+
+impl Square {
+    pub fn new(amplitude: f64, frequency: f64, phase: f64, bias: f64) -> Self {
+        Self {
+            amplitude,
+            frequency,
+            phase,
+            bias,
+        }
+    }
+
+    pub fn amplitude(&self) -> f64 {
+        self.amplitude
+    }
+
+    pub fn frequency(&self) -> f64 {
+        self.frequency
+    }
+
+    pub fn phase(&self) -> f64 {
+        self.phase
+    }
+
+    pub fn bias(&self) -> f64 {
+        self.bias
+    }
+
+    // Fraction of the current period elapsed, in [0, 1).
+    fn cycle(&self, t: f64) -> f64 {
+        (self.frequency * t + self.phase / (2.0 * PI)).rem_euclid(1.0)
+    }
+}
+
+impl Source<f64> for Square {
+    fn output(&self, t: f64) -> f64 {
+        if self.cycle(t) < 0.5 {
+            self.bias + self.amplitude
+        } else {
+            self.bias - self.amplitude
+        }
+    }
+
+    fn zero_crossings(&self, t: f64) -> Vec<f64> {
+        vec![(2.0 * PI * self.frequency * t + self.phase).sin()]
+    }
+}
+
+impl Block<f64> for Square {
+    fn num_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        vec![Source::output(self, t)]
+    }
+
+    fn zero_crossings(&self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        Source::zero_crossings(self, t)
+    }
+}
+
+// Rises linearly from `bias - amplitude` to `bias + amplitude` over each
+// period.
+#[derive(Debug, Clone)]
+pub struct Sawtooth {
+    amplitude: f64,
+    frequency: f64,
+    phase: f64,
+    bias: f64,
+}
+
+impl Sawtooth {
+    pub fn new(amplitude: f64, frequency: f64, phase: f64, bias: f64) -> Self {
+        Self {
+            amplitude,
+            frequency,
+            phase,
+            bias,
+        }
+    }
+
+    pub fn amplitude(&self) -> f64 {
+        self.amplitude
+    }
+
+    pub fn frequency(&self) -> f64 {
+        self.frequency
+    }
+
+    pub fn phase(&self) -> f64 {
+        self.phase
+    }
+
+    pub fn bias(&self) -> f64 {
+        self.bias
+    }
+}
+
+impl Source<f64> for Sawtooth {
+    fn output(&self, t: f64) -> f64 {
+        let cycle = (self.frequency * t + self.phase / (2.0 * PI)).rem_euclid(1.0);
+        self.bias + self.amplitude * (2.0 * cycle - 1.0)
+    }
+
+    // Changes sign at every wrap of the sawtooth.
+    fn zero_crossings(&self, t: f64) -> Vec<f64> {
+        vec![(PI * self.frequency * t + 0.5 * self.phase).sin()]
+    }
+}
+
+impl Block<f64> for Sawtooth {
+    fn num_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        vec![Source::output(self, t)]
+    }
+
+    fn zero_crossings(&self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        Source::zero_crossings(self, t)
+    }
+}
+
+// Outputs `amplitude` for the first `duty` fraction of each period and zero
+// otherwise, starting at `delay`.
+#[derive(Debug, Clone)]
+pub struct Pulse {
+    amplitude: f64,
+    period: f64,
+    duty: f64,
+    delay: f64,
+}
+
+impl Pulse {
+    pub fn new(amplitude: f64, period: f64, duty: f64, delay: f64) -> Result<Self, SimError> {
+        if !(period > 0.0 && period.is_finite()) {
+            return Err(SimError::InvalidParameter(format!(
+                "pulse period must be positive and finite, got {period}"
+            )));
+        }
+        if !(0.0..=1.0).contains(&duty) {
+            return Err(SimError::InvalidParameter(format!(
+                "pulse duty must be within [0, 1], got {duty}"
+            )));
+        }
+        Ok(Self {
+            amplitude,
+            period,
+            duty,
+            delay,
+        })
+    }
+
+    pub fn amplitude(&self) -> f64 {
+        self.amplitude
+    }
+
+    pub fn period(&self) -> f64 {
+        self.period
+    }
+
+    pub fn duty(&self) -> f64 {
+        self.duty
+    }
+
+    pub fn delay(&self) -> f64 {
+        self.delay
+    }
+}
+
+impl Source<f64> for Pulse {
+    fn output(&self, t: f64) -> f64 {
+        if t >= self.delay && (t - self.delay).rem_euclid(self.period) < self.duty * self.period {
+            self.amplitude
+        } else {
+            0.0
+        }
+    }
+
+    // Rising edges, then falling edges. Nothing switches before the delay,
+    // where the first rising edge is.
+    fn zero_crossings(&self, t: f64) -> Vec<f64> {
+        if t < self.delay {
+            return vec![t - self.delay, (-PI * self.duty).sin()];
+        }
+        let rise = (t - self.delay) / self.period;
+        let fall = rise - self.duty;
+        vec![(PI * rise).sin(), (PI * fall).sin()]
+    }
+}
+
+impl Block<f64> for Pulse {
+    fn num_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        vec![Source::output(self, t)]
+    }
+
+    fn zero_crossings(&self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        Source::zero_crossings(self, t)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChirpMethod {
+    Linear,
+    Logarithmic,
+}
+
+// Sweeps the instantaneous frequency from `f0` at t = 0 to `f1` at `t1`, and
+// keeps sweeping at the same law afterwards. The logarithmic sweep needs both
+// frequencies to be positive.
+#[derive(Debug, Clone)]
+pub struct Chirp {
+    amplitude: f64,
+    f0: f64,
+    f1: f64,
+    t1: f64,
+    method: ChirpMethod,
+}
+
+impl Chirp {
+    pub fn new(
+        amplitude: f64,
+        f0: f64,
+        f1: f64,
+        t1: f64,
+        method: ChirpMethod,
+    ) -> Result<Self, SimError> {
+        if !(t1 > 0.0 && t1.is_finite()) {
+            return Err(SimError::InvalidParameter(format!(
+                "chirp target time must be positive and finite, got {t1}"
+            )));
+        }
+        if !(f0.is_finite() && f1.is_finite()) {
+            return Err(SimError::InvalidParameter(format!(
+                "chirp frequencies must be finite, got {f0} and {f1}"
+            )));
+        }
+        if method == ChirpMethod::Logarithmic && !(f0 > 0.0 && f1 > 0.0) {
+            return Err(SimError::InvalidParameter(format!(
+                "logarithmic chirp frequencies must be positive, got {f0} and {f1}"
+            )));
+        }
+        Ok(Self {
+            amplitude,
+            f0,
+            f1,
+            t1,
+            method,
+        })
+    }
+
+    pub fn linear(amplitude: f64, f0: f64, f1: f64, t1: f64) -> Result<Self, SimError> {
+        Self::new(amplitude, f0, f1, t1, ChirpMethod::Linear)
+    }
+
+    pub fn logarithmic(amplitude: f64, f0: f64, f1: f64, t1: f64) -> Result<Self, SimError> {
+        Self::new(amplitude, f0, f1, t1, ChirpMethod::Logarithmic)
+    }
+
+    pub fn amplitude(&self) -> f64 {
+        self.amplitude
+    }
+
+    pub fn f0(&self) -> f64 {
+        self.f0
+    }
+
+    pub fn f1(&self) -> f64 {
+        self.f1
+    }
+
+    pub fn t1(&self) -> f64 {
+        self.t1
+    }
+
+    pub fn method(&self) -> ChirpMethod {
+        self.method
+    }
+
+    pub fn frequency(&self, t: f64) -> f64 {
+        match self.method {
+            ChirpMethod::Linear => self.f0 + (self.f1 - self.f0) * t / self.t1,
+            ChirpMethod::Logarithmic => self.f0 * (self.f1 / self.f0).powf(t / self.t1),
+        }
+    }
+
+    // Integral of the instantaneous frequency from 0 to `t`, in cycles.
+    fn cycles(&self, t: f64) -> f64 {
+        match self.method {
+            ChirpMethod::Linear => self.f0 * t + 0.5 * (self.f1 - self.f0) * t * t / self.t1,
+            ChirpMethod::Logarithmic => {
+                let k = self.f1 / self.f0;
+                if (k - 1.0).abs() < f64::EPSILON {
+                    self.f0 * t
+                } else {
+                    self.f0 * self.t1 * (k.powf(t / self.t1) - 1.0) / k.ln()
+                }
+            }
+        }
+    }
+}
+
+impl Source<f64> for Chirp {
+    fn output(&self, t: f64) -> f64 {
+        self.amplitude * (2.0 * PI * self.cycles(t)).sin()
+    }
+}
+
+impl Block<f64> for Chirp {
+    fn num_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, t: f64, _inputs: &[f64]) -> Vec<f64> {
+        vec![Source::output(self, t)]
+    }
+}
+
+// Drives one scalar source per channel, so any of the generators above can
+// excite a multi-channel input. Channels of different types are boxed, which
+// is the default.
+#[cfg(feature = "vector")]
+pub(crate) mod vector {
+    use crate::{block::Source, model::Vector};
+
+    #[derive(Debug, Clone)]
+    pub struct VectorSource<S = Box<dyn Source<f64>>> {
+        channels: Vec<S>,
+    }
+
+    impl VectorSource {
+        pub fn mixed() -> Self {
+            Self::new(Vec::new())
+        }
+
+        pub fn with(mut self, channel: impl Source<f64> + 'static) -> Self {
+            self.channels.push(Box::new(channel));
+            self
+        }
+    }
+
+    impl<S: Source<f64>> VectorSource<S> {
+        pub fn new(channels: Vec<S>) -> Self {
+            Self { channels }
+        }
+
+        pub fn channels(&self) -> &[S] {
+            &self.channels
+        }
+
+        pub fn channels_mut(&mut self) -> &mut [S] {
+            &mut self.channels
+        }
+    }
+
+    impl<S: Source<f64>> Source<Vector> for VectorSource<S> {
+        fn output(&self, t: f64) -> Vector {
+            Vector::from(
+                self.channels
+                    .iter()
+                    .map(|s| s.output(t))
+                    .collect::<Vec<_>>(),
+            )
+        }
+
+        fn zero_crossings(&self, t: f64) -> Vec<f64> {
+            self.channels
+                .iter()
+                .flat_map(|s| s.zero_crossings(t))
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod source_tests {
+    use super::*;
+
+    #[test]
+    fn test_periodic() {
+        let sine = Sine::new(2.0, 0.5, 0.0, 1.0);
+        assert!((Source::output(&sine, 0.5) - 3.0).abs() < 1e-12);
+        let square = Square::new(1.0, 1.0, 0.0, 0.0);
+        assert_eq!(Source::output(&square, 0.25), 1.0);
+        assert_eq!(Source::output(&square, 0.75), -1.0);
+        let saw = Sawtooth::new(1.0, 2.0, 0.0, 0.0);
+        assert!((Source::output(&saw, 0.25)).abs() < 1e-12);
+        assert!((Source::output(&saw, 0.5) + 1.0).abs() < 1e-12);
+        let ramp = Ramp::new(2.0, 1.0, 0.5);
+        assert_eq!(Source::output(&ramp, 0.0), 0.5);
+        assert_eq!(Source::output(&ramp, 2.0), 2.5);
+    }
+
+    #[test]
+    fn test_pulse() {
+        let pulse = Pulse::new(3.0, 1.0, 0.25, 0.5).unwrap();
+        let out = [0.0, 0.6, 0.8, 1.6, 2.4]
+            .iter()
+            .map(|t| Source::output(&pulse, *t))
+            .collect::<Vec<_>>();
+        assert_eq!(out, vec![0.0, 3.0, 0.0, 3.0, 0.0]);
+        let g0 = Block::zero_crossings(&pulse, 0.7, &[]);
+        let g1 = Block::zero_crossings(&pulse, 0.8, &[]);
+        assert!(g0[1] * g1[1] < 0.0);
+        assert!(g0[0] * g1[0] > 0.0);
+        // No edges before the delay, and the first rising edge at it.
+        let before = [0.0, 0.2, 0.4].map(|t| Block::zero_crossings(&pulse, t, &[]));
+        assert!(before.windows(2).all(|w| w[0][1] == w[1][1]));
+        assert!(before.iter().all(|g| g[0] < 0.0));
+        assert!(Block::zero_crossings(&pulse, 0.5, &[])[0] >= 0.0);
+        assert!(Pulse::new(1.0, 0.0, 0.5, 0.0).is_err());
+        assert!(Pulse::new(1.0, f64::NAN, 0.5, 0.0).is_err());
+        assert!(Pulse::new(1.0, 1.0, 1.5, 0.0).is_err());
+        assert!(Pulse::new(1.0, 1.0, f64::NAN, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_chirp() {
+        let linear = Chirp::linear(1.0, 1.0, 3.0, 2.0).unwrap();
+        assert!((linear.frequency(1.0) - 2.0).abs() < 1e-12);
+        // cycles(2) = 2 + 2 = 4, a whole number of periods.
+        assert!(Source::output(&linear, 2.0).abs() < 1e-9);
+        let log = Chirp::logarithmic(1.0, 1.0, 100.0, 2.0).unwrap();
+        assert!((log.frequency(1.0) - 10.0).abs() < 1e-9);
+        let h = 1e-6;
+        let rate = (log.cycles(1.0 + h) - log.cycles(1.0 - h)) / (2.0 * h);
+        assert!((rate - 10.0).abs() < 1e-5);
+        assert!(Chirp::linear(1.0, 1.0, 3.0, 0.0).is_err());
+        assert!(Chirp::linear(1.0, 1.0, 3.0, f64::INFINITY).is_err());
+        assert!(Chirp::linear(1.0, f64::NAN, 3.0, 1.0).is_err());
+        assert!(Chirp::linear(1.0, 0.0, 3.0, 1.0).is_ok());
+        assert!(Chirp::logarithmic(1.0, 0.0, 3.0, 1.0).is_err());
+        assert!(Chirp::logarithmic(1.0, 1.0, -3.0, 1.0).is_err());
+    }
+
+    #[cfg(feature = "vector")]
+    #[test]
+    fn test_vector_source() {
+        use crate::model::Vector;
+
+        let s =
+            vector::VectorSource::new(vec![Ramp::new(1.0, 0.0, 0.0), Ramp::new(-1.0, 1.0, 2.0)]);
+        let v: Vector = s.output(2.0);
+        assert_eq!(v, Vector::from(vec![2.0, 1.0]));
+
+        let s = vector::VectorSource::mixed()
+            .with(Step::new(0.0, 1.0, 1.0))
+            .with(Sine::new(1.0, 0.25, 0.0, 0.0));
+        let v: Vector = s.output(1.0);
+        assert_eq!(v, Vector::from(vec![1.0, 1.0]));
+        assert_eq!(Source::<Vector>::zero_crossings(&s, 1.5), vec![0.5]);
+    }
 }
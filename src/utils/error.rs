@@ -0,0 +1,116 @@
+use std::{error::Error, fmt, io};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimError {
+    DimensionMismatch {
+        expected: usize,
+        found: usize,
+    },
+    // (rows, cols) of two matrices that must have the same shape.
+    ShapeMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    EmptyVector,
+    EmptyMatrix,
+    NotSquare {
+        rows: usize,
+        cols: usize,
+    },
+    SingularMatrix,
+    NanEncountered {
+        t: f64,
+        block: String,
+    },
+    UnknownBlock(usize),
+    PortOutOfRange {
+        block: String,
+        port: usize,
+        ports: usize,
+    },
+    InputAlreadyConnected {
+        block: String,
+        port: usize,
+    },
+    UnconnectedInput {
+        block: String,
+        port: usize,
+    },
+    AlgebraicLoop(Vec<String>),
+    InvalidParameter(String),
+    SolverFailure(String),
+    OutOfRange {
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+    Io(String),
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimError::DimensionMismatch { expected, found } => {
+                write!(
+                    f,
+                    "dimension mismatch: expected {}, found {}",
+                    expected, found
+                )
+            }
+            SimError::ShapeMismatch { expected, found } => write!(
+                f,
+                "shape mismatch: expected {} x {}, found {} x {}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            SimError::EmptyVector => write!(f, "vector is empty"),
+            SimError::EmptyMatrix => write!(f, "matrix is empty"),
+            SimError::NotSquare { rows, cols } => {
+                write!(f, "matrix is not square: {} x {}", rows, cols)
+            }
+            SimError::SingularMatrix => write!(f, "matrix is singular"),
+            SimError::NanEncountered { t, block } => {
+                write!(f, "NaN encountered in block `{}` at t = {}", block, t)
+            }
+            SimError::UnknownBlock(id) => write!(f, "unknown block id {}", id),
+            SimError::PortOutOfRange { block, port, ports } => write!(
+                f,
+                "port {} of block `{}` is out of range ({} ports)",
+                port, block, ports
+            ),
+            SimError::InputAlreadyConnected { block, port } => {
+                write!(
+                    f,
+                    "input {} of block `{}` is already connected",
+                    port, block
+                )
+            }
+            SimError::UnconnectedInput { block, port } => {
+                write!(f, "input {} of block `{}` is not connected", port, block)
+            }
+            SimError::AlgebraicLoop(blocks) => {
+                write!(f, "algebraic loop: {}", blocks.join(" -> "))
+            }
+            SimError::InvalidParameter(msg) => write!(f, "invalid parameter: {}", msg),
+            SimError::SolverFailure(msg) => write!(f, "solver failure: {}", msg),
+            SimError::OutOfRange { value, min, max } => {
+                write!(f, "value {} is outside the range [{}, {}]", value, min, max)
+            }
+            SimError::Io(msg) => write!(f, "io error: {}", msg),
+            SimError::Parse { line, message } => {
+                write!(f, "parse error on line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl Error for SimError {}
+
+impl From<io::Error> for SimError {
+    fn from(e: io::Error) -> Self {
+        SimError::Io(e.to_string())
+    }
+}
@@ -0,0 +1,74 @@
+// Blocks shared by the tests across modules; not every feature subset uses
+// every one of them.
+#![allow(dead_code)]
+
+use crate::block::{Block, ContinuousState, Source, Transfer};
+
+// The simulation time itself.
+pub(crate) struct Ramp;
+
+impl Source<f64> for Ramp {
+    fn output(&self, t: f64) -> f64 {
+        t
+    }
+}
+
+pub(crate) struct Constant(pub f64);
+
+impl Source<f64> for Constant {
+    fn output(&self, _t: f64) -> f64 {
+        self.0
+    }
+}
+
+pub(crate) struct Gain(pub f64);
+
+impl Transfer<f64, f64> for Gain {
+    fn transfer(&self, _t: f64, input: &f64) -> f64 {
+        self.0 * input
+    }
+}
+
+// The first-order plant dy/dt = u - y, with no direct feedthrough so it can
+// close a loop.
+pub(crate) struct Lag(pub f64);
+
+impl Block<f64> for Lag {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn output(&mut self, _t: f64, _inputs: &[f64]) -> Vec<f64> {
+        vec![self.0]
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        false
+    }
+
+    fn continuous_state(&self) -> Option<&dyn ContinuousState<f64>> {
+        Some(self)
+    }
+
+    fn continuous_state_mut(&mut self) -> Option<&mut dyn ContinuousState<f64>> {
+        Some(self)
+    }
+}
+
+impl ContinuousState<f64> for Lag {
+    fn state(&self) -> Vec<f64> {
+        vec![self.0]
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        self.0 = state[0];
+    }
+
+    fn derivative(&self, _t: f64, x: &[f64], u: &[f64]) -> Vec<f64> {
+        vec![u[0] - x[0]]
+    }
+}
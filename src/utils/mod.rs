@@ -1,4 +1,8 @@
 pub(crate) mod error;
 pub(crate) mod logger;
 
+#[cfg(test)]
+pub(crate) mod fixtures;
+
+pub use error::*;
 pub use logger::*;